@@ -0,0 +1,49 @@
+// Benchmark de la construcción de la BVH sobre una malla de 100k triángulos, en un solo hilo contra
+// todos los núcleos. Los dos árboles son idénticos; solo cambia el tiempo que tardan en armarse.
+
+use criterion::{criterion_group, criterion_main, Criterion};
+use nalgebra_glm::Vec3;
+use ray_tracing::bvh::Bvh;
+use ray_tracing::intersect::RayIntersect;
+use ray_tracing::material::Material;
+use ray_tracing::triangle::Triangle;
+
+// Lado de la cuadrícula: 224 x 224 celdas de dos triángulos son poco más de 100k triángulos
+const GRID: usize = 224;
+
+// Terreno ondulado como el de una malla importada, con dos triángulos por celda
+fn terrain_mesh() -> Vec<Box<dyn RayIntersect>> {
+    let vertex = |x: usize, z: usize| {
+        let (fx, fz) = (x as f32 * 0.1, z as f32 * 0.1);
+        Vec3::new(fx, (fx * 0.7).sin() * (fz * 0.5).cos(), fz)
+    };
+    let material = Material::matte_white();
+    let mut triangles: Vec<Box<dyn RayIntersect>> = Vec::with_capacity(GRID * GRID * 2);
+    for z in 0..GRID {
+        for x in 0..GRID {
+            let (a, b, c, d) = (vertex(x, z), vertex(x + 1, z), vertex(x + 1, z + 1), vertex(x, z + 1));
+            triangles.push(Box::new(Triangle::new([a, b, c], material.clone())));
+            triangles.push(Box::new(Triangle::new([a, c, d], material.clone())));
+        }
+    }
+    triangles
+}
+
+fn bench_bvh_build(c: &mut Criterion) {
+    // Con un solo hilo en el pool `Bvh::new` no reparte el trabajo, así que mide la construcción en serie
+    let serial_pool = rayon::ThreadPoolBuilder::new().num_threads(1).build().expect("thread pool");
+
+    let mut group = c.benchmark_group("bvh_build");
+    // Cada construcción tarda del orden de cien milisegundos; 10 muestras bastan
+    group.sample_size(10);
+    group.bench_function("serial_100k_triangles", |b| {
+        b.iter_batched(terrain_mesh, |mesh| serial_pool.install(|| Bvh::new(mesh)), criterion::BatchSize::LargeInput)
+    });
+    group.bench_function("parallel_100k_triangles", |b| {
+        b.iter_batched(terrain_mesh, Bvh::new, criterion::BatchSize::LargeInput)
+    });
+    group.finish();
+}
+
+criterion_group!(benches, bench_bvh_build);
+criterion_main!(benches);
@@ -0,0 +1,103 @@
+// Benchmark de un cuadro completo sobre una escena fija: cubos texturizados, una esfera reflectante
+// y una luz con sombras. Criterion informa el tiempo por cuadro y, como throughput, los rayos por segundo.
+
+use std::sync::Arc;
+
+use criterion::{criterion_group, criterion_main, Criterion, Throughput};
+use nalgebra_glm::Vec3;
+use ray_tracing::bvh::Bvh;
+use ray_tracing::camera::{Camera, DEFAULT_FOV};
+use ray_tracing::color::Color;
+use ray_tracing::cube::Cube;
+use ray_tracing::intersect::RayIntersect;
+use ray_tracing::light::Light;
+use ray_tracing::material::Material;
+use ray_tracing::render::{auto_chunk_size, render, RenderConfig};
+use ray_tracing::sphere::Sphere;
+use ray_tracing::texture::Texture;
+
+const WIDTH: usize = 320;
+const HEIGHT: usize = 240;
+
+// Tablero de ajedrez de 8x8 generado en memoria, para no depender de archivos
+fn checker_texture() -> Texture {
+    let data = (0..64)
+        .map(|i| if (i % 8 + i / 8) % 2 == 0 { Color::new(200, 170, 120) } else { Color::new(90, 140, 60) })
+        .collect();
+    Texture::new(8, 8, data)
+}
+
+fn standard_scene() -> (Vec<Box<dyn RayIntersect>>, Vec<Light>, Camera) {
+    let mut ground = Material {
+        diffuse: Color::new(255, 255, 255),
+        specular: 30.0,
+        albedo: [0.8, 0.2, 0.0, 0.0],
+        ..Material::black()
+    };
+    ground.texture = Some(Arc::new(checker_texture()));
+
+    let mirror = Material {
+        diffuse: Color::new(220, 220, 230),
+        specular: 200.0,
+        albedo: [0.3, 0.4, 0.6, 0.0],
+        refractive_index: 1.5,
+        ..Material::black()
+    };
+
+    // Una capa de 8x8 cubos de suelo y unas columnas que proyectan sombras sobre él
+    let mut objects: Vec<Box<dyn RayIntersect>> = Vec::new();
+    for x in 0..8 {
+        for z in 0..8 {
+            let center = Vec3::new(x as f32 * 2.0 - 7.0, -1.0, -(z as f32) * 2.0);
+            objects.push(Box::new(Cube::uniform(center, 2.0, ground.clone())));
+        }
+    }
+    for (x, z) in [(-4.0, -4.0), (4.0, -6.0), (0.0, -10.0)] {
+        for level in 0..3 {
+            objects.push(Box::new(Cube::uniform(Vec3::new(x, 1.0 + level as f32 * 2.0, z), 2.0, ground.clone())));
+        }
+    }
+    objects.push(Box::new(Sphere::new(Vec3::new(0.0, 1.5, -5.0), 1.5, mirror)));
+
+    let lights = vec![
+        Light::new(Vec3::zeros(), Color::new(255, 255, 255), 0.2),
+        Light::new(Vec3::new(6.0, 12.0, 4.0), Color::new(255, 255, 255), 1.2),
+    ];
+
+    let camera = Camera {
+        eye: Vec3::new(0.0, 6.0, 8.0),
+        center: Vec3::new(0.0, 0.0, -6.0),
+        up: Vec3::new(0.0, 1.0, 0.0),
+        fov: DEFAULT_FOV,
+        lens: None,
+    };
+
+    (objects, lights, camera)
+}
+
+fn bench_render(c: &mut Criterion) {
+    let (objects, lights, camera) = standard_scene();
+    let config = RenderConfig { chunk_size: auto_chunk_size(HEIGHT, &RenderConfig::default()), ..RenderConfig::default() };
+    let mut framebuffer = vec![0u32; WIDTH * HEIGHT];
+
+    // La escena es determinista: el número de rayos de un cuadro sirve de throughput para todos
+    let rays = render(&mut framebuffer, WIDTH, HEIGHT, &objects, &camera, &lights, &config).rays;
+
+    let mut group = c.benchmark_group("render");
+    group.throughput(Throughput::Elements(rays));
+    // Un cuadro tarda decenas de milisegundos: con 20 muestras la medición sigue siendo estable
+    group.sample_size(20);
+    group.bench_function("standard_scene_320x240", |b| {
+        b.iter(|| render(&mut framebuffer, WIDTH, HEIGHT, &objects, &camera, &lights, &config))
+    });
+
+    // La misma escena con los objetos dentro de una BVH, contra el recorrido lineal de arriba
+    let accelerated: Vec<Box<dyn RayIntersect>> = vec![Box::new(Bvh::new(objects))];
+    group.bench_function("standard_scene_bvh_320x240", |b| {
+        b.iter(|| render(&mut framebuffer, WIDTH, HEIGHT, &accelerated, &camera, &lights, &config))
+    });
+    group.finish();
+}
+
+criterion_group!(benches, bench_render);
+criterion_main!(benches);
@@ -0,0 +1,70 @@
+use nalgebra_glm::Vec3;
+
+// Caja delimitadora alineada a los ejes
+#[derive(Debug, Clone, Copy)]
+pub struct Aabb {
+    pub min: Vec3,
+    pub max: Vec3,
+}
+
+impl Aabb {
+    pub fn new(min: Vec3, max: Vec3) -> Self {
+        Aabb { min, max }
+    }
+
+    // La caja más chica que contiene a las dos
+    pub fn union(&self, other: &Aabb) -> Aabb {
+        Aabb::new(self.min.inf(&other.min), self.max.sup(&other.max))
+    }
+
+    // Distancias a las que el rayo entra y sale de la caja (método de las placas); si la entrada queda
+    // después de la salida, el rayo no la cruza. `inv_dir` es 1 / dirección por componente, calculada
+    // una sola vez por rayo para probar muchas cajas con productos en vez de divisiones.
+    pub fn slab_interval(&self, origin: &Vec3, inv_dir: &Vec3) -> (f32, f32) {
+        let t_slab_min = (self.min - origin).component_mul(inv_dir);
+        let t_slab_max = (self.max - origin).component_mul(inv_dir);
+
+        let t1 = t_slab_min.x.min(t_slab_max.x).max(t_slab_min.y.min(t_slab_max.y)).max(t_slab_min.z.min(t_slab_max.z));
+        let t2 = t_slab_min.x.max(t_slab_max.x).min(t_slab_min.y.max(t_slab_max.y)).min(t_slab_min.z.max(t_slab_max.z));
+        (t1, t2)
+    }
+
+    // Distancia a la que el rayo entra a la caja (0 si empieza dentro), o None si no la cruza hacia adelante
+    pub fn intersects_ray(&self, origin: &Vec3, inv_dir: &Vec3) -> Option<f32> {
+        let (t1, t2) = self.slab_interval(origin, inv_dir);
+        (t1 <= t2 && t2 >= 0.0).then_some(t1.max(0.0))
+    }
+
+    // false para las cajas de objetos infinitos, como un plano
+    pub fn is_finite(&self) -> bool {
+        self.min.iter().chain(self.max.iter()).all(|c| c.is_finite())
+    }
+
+    // Las 8 esquinas; el bit 0 del índice elige x, el bit 1 elige y y el bit 2 elige z
+    pub fn corners(&self) -> [Vec3; 8] {
+        let mut corners = [Vec3::zeros(); 8];
+        for (i, corner) in corners.iter_mut().enumerate() {
+            *corner = Vec3::new(
+                if i & 1 == 0 { self.min.x } else { self.max.x },
+                if i & 2 == 0 { self.min.y } else { self.max.y },
+                if i & 4 == 0 { self.min.z } else { self.max.z },
+            );
+        }
+        corners
+    }
+
+    // Las 12 aristas como pares de índices de esquinas que difieren en un solo eje
+    pub fn edges() -> [(usize, usize); 12] {
+        let mut edges = [(0, 0); 12];
+        let mut count = 0;
+        for i in 0..8 {
+            for bit in [1, 2, 4] {
+                if i & bit == 0 {
+                    edges[count] = (i, i | bit);
+                    count += 1;
+                }
+            }
+        }
+        edges
+    }
+}
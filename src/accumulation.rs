@@ -0,0 +1,61 @@
+use rayon::prelude::*;
+
+use crate::camera::Camera;
+use crate::color::Color;
+use crate::intersect::RayIntersect;
+use crate::light::Light;
+use crate::render::{firefly_scale, in_thread_pool, post_process, primary_ray, trace_camera_ray, RenderConfig};
+use crate::sampling::pixel_jitter;
+
+// Supermuestreo temporal: mientras la cámara está quieta, cada cuadro suma una muestra desplazada
+// por píxel a un buffer en punto flotante y la imagen converge al promedio de todas. Cualquier
+// cambio en la cámara o la escena debe llamar a `reset`.
+pub struct Accumulator {
+    pub width: usize,
+    pub height: usize,
+    sums: Vec<[f32; 3]>,
+    samples: u32,
+}
+
+impl Accumulator {
+    pub fn new(width: usize, height: usize) -> Self {
+        Accumulator { width, height, sums: vec![[0.0; 3]; width * height], samples: 0 }
+    }
+
+    // Muestras acumuladas por píxel hasta ahora
+    pub fn samples(&self) -> u32 {
+        self.samples
+    }
+
+    pub fn reset(&mut self) {
+        self.sums.iter_mut().for_each(|sum| *sum = [0.0; 3]);
+        self.samples = 0;
+    }
+
+    // Lanza un rayo por píxel con el desplazamiento de la siguiente muestra y lo suma al buffer
+    pub fn add_sample(&mut self, objects: &[Box<dyn RayIntersect>], camera: &Camera, lights: &[Light], config: &RenderConfig) {
+        let (width, height, sample) = (self.width, self.height, self.samples);
+        let sums = &mut self.sums;
+        in_thread_pool(config, || sums.par_iter_mut().enumerate().for_each(|(i, sum)| {
+            let (x, y) = (i % width, i / width);
+            let (jitter_x, jitter_y) = pixel_jitter(x, y, sample);
+            let (ray_origin, ray_direction) =
+                primary_ray(camera, config.projection, x as f32 + jitter_x, y as f32 + jitter_y, width, height);
+            let color = trace_camera_ray::<false>(&ray_origin, &ray_direction, objects, lights, camera, config, height).color;
+            let scale = firefly_scale(color, config.firefly_clamp);
+            sum[0] += color.r as f32 * scale;
+            sum[1] += color.g as f32 * scale;
+            sum[2] += color.b as f32 * scale;
+        }));
+        self.samples += 1;
+    }
+
+    // Escribe en `framebuffer` el promedio de las muestras, con el mismo postproceso que `render`
+    pub fn resolve(&self, framebuffer: &mut [u32], config: &RenderConfig) {
+        let n = self.samples.max(1) as f32;
+        in_thread_pool(config, || framebuffer.par_iter_mut().zip(self.sums.par_iter()).enumerate().for_each(|(i, (pixel, sum))| {
+            let average = sum.map(|channel| (channel / n).round().min(255.0) as u8);
+            *pixel = post_process(Color::new(average[0], average[1], average[2]), config, (i % self.width, i / self.width));
+        }));
+    }
+}
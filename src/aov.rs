@@ -0,0 +1,67 @@
+use rayon::prelude::*;
+
+use crate::camera::Camera;
+use crate::color::Color;
+use crate::intersect::RayIntersect;
+use crate::light::Light;
+use crate::render::{in_thread_pool, primary_ray, trace_camera_ray, RenderConfig, ShadingBreakdown};
+
+// Una imagen por cada parte del sombreado (difusa, especular, sombra, reflejo...), para componerlas
+// por separado o ver de dónde sale el color de un píxel. Cada capa es un framebuffer 0RGB sin
+// postproceso (ni exposición ni operador de tono).
+pub struct Aovs {
+    pub width: usize,
+    pub height: usize,
+    pub pixels: Vec<ShadingBreakdown>,
+}
+
+impl Aovs {
+    // Lanza un rayo primario por píxel (en la misma posición que `render` con una muestra)
+    pub fn render(
+        width: usize,
+        height: usize,
+        objects: &[Box<dyn RayIntersect>],
+        camera: &Camera,
+        lights: &[Light],
+        config: &RenderConfig,
+    ) -> Self {
+        let pixels = in_thread_pool(config, || {
+            (0..width * height)
+                .into_par_iter()
+                .map(|i| {
+                    let (ray_origin, ray_direction) =
+                        primary_ray(camera, config.projection, (i % width) as f32, (i / width) as f32, width, height);
+                    trace_camera_ray::<true>(&ray_origin, &ray_direction, objects, lights, camera, config, height)
+                })
+                .collect()
+        });
+
+        Aovs { width, height, pixels }
+    }
+
+    // Framebuffer 0RGB con la parte que elige `part` en cada píxel
+    pub fn layer(&self, part: impl Fn(&ShadingBreakdown) -> Color) -> Vec<u32> {
+        self.pixels
+            .iter()
+            .map(|pixel| {
+                let color = part(pixel);
+                ((color.r as u32) << 16) | ((color.g as u32) << 8) | color.b as u32
+            })
+            .collect()
+    }
+
+    // Todas las capas con su nombre, en el orden en que se suelen componer
+    pub fn layers(&self) -> Vec<(&'static str, Vec<u32>)> {
+        vec![
+            ("color", self.layer(|pixel| pixel.color)),
+            ("background", self.layer(|pixel| pixel.background)),
+            ("ambient", self.layer(|pixel| pixel.ambient)),
+            ("diffuse", self.layer(|pixel| pixel.diffuse)),
+            ("specular", self.layer(|pixel| pixel.specular)),
+            ("shadow", self.layer(|pixel| pixel.shadow)),
+            ("reflection", self.layer(|pixel| pixel.reflection)),
+            ("refraction", self.layer(|pixel| pixel.refraction)),
+            ("emission", self.layer(|pixel| pixel.emission)),
+        ]
+    }
+}
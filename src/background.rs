@@ -0,0 +1,107 @@
+use std::f32::consts::PI;
+
+use nalgebra_glm::Vec3;
+use crate::color::Color;
+use crate::cube::Cube;
+use crate::texture::{load_texture, Texture};
+
+// Lo que ven los rayos que no chocan con ningún objeto
+#[derive(Debug, Clone)]
+pub enum Background {
+    Solid(Color),
+    Equirectangular(Texture),  // Mapa de entorno en proyección equirrectangular (longitud x latitud)
+    Gradient { horizon: Color, zenith: Color },  // Cielo que pasa del horizonte al cénit según la altura
+    CubeMap(Box<CubeMap>),     // Cielo formado por seis imágenes, una por cara de un cubo
+}
+
+// Seis texturas en el mismo orden de caras que `Cube`: X-, X+, Y+, Y-, Z+, Z-
+#[derive(Debug, Clone)]
+pub struct CubeMap {
+    pub faces: [Texture; 6],
+}
+
+impl CubeMap {
+    pub fn new(faces: [Texture; 6]) -> Self {
+        CubeMap { faces }
+    }
+
+    // Carga las seis caras desde archivos, en el orden X-, X+, Y+, Y-, Z+, Z-
+    pub fn load(paths: [&str; 6]) -> Self {
+        CubeMap { faces: paths.map(load_texture) }
+    }
+
+    // El eje dominante de la dirección elige la cara; las UV se calculan igual que en `Cube`
+    pub fn sample(&self, direction: &Vec3) -> Color {
+        let abs = direction.abs();
+        let (face_index, dominant) = if abs.x >= abs.y && abs.x >= abs.z {
+            (if direction.x < 0.0 { 0 } else { 1 }, abs.x)
+        } else if abs.y >= abs.z {
+            (if direction.y > 0.0 { 2 } else { 3 }, abs.y)
+        } else {
+            (if direction.z > 0.0 { 4 } else { 5 }, abs.z)
+        };
+
+        // Proyectamos la dirección sobre la cara de un cubo de lado 2 centrado en el origen
+        let local_pos = direction / dominant;
+        let (u, v) = Cube::get_uv_for_face(face_index, local_pos);
+
+        // Las caras se ven desde adentro, así que reflejamos u para que la imagen no quede al revés
+        self.faces[face_index].sample(1.0 - u, v)
+    }
+}
+
+impl Background {
+    pub fn sample(&self, direction: &Vec3) -> Color {
+        match self {
+            Background::Solid(color) => *color,
+            Background::Equirectangular(texture) => {
+                let direction = direction.normalize();
+                // u recorre la longitud alrededor del eje Y; v va de 0 (abajo) a 1 (arriba)
+                let u = 0.5 + direction.x.atan2(-direction.z) / (2.0 * PI);
+                let v = 0.5 + direction.y.clamp(-1.0, 1.0).asin() / PI;
+                sample_bilinear_equirectangular(texture, u, v)
+            }
+            Background::Gradient { horizon, zenith } => {
+                // Bajo el horizonte se repite su color para que el suelo lejano no se vea negro
+                let height = direction.normalize().y.max(0.0);
+                horizon.lerp(zenith, height.sqrt())
+            }
+            Background::CubeMap(cube_map) => cube_map.sample(direction),
+        }
+    }
+
+    // Cielo diurno para el sol a la elevación dada (en grados): más cálido cuanto más bajo está el sol
+    pub fn daylight_sky(elevation: f32) -> Self {
+        let day = elevation.to_radians().sin().clamp(0.0, 1.0);
+        Background::Gradient {
+            horizon: Color::new(250, 170, 120).lerp(&Color::new(190, 215, 240), day),
+            zenith: Color::new(40, 60, 110).lerp(&Color::new(70, 130, 220), day),
+        }
+    }
+
+    // Cielo de seis caras cargado desde archivos, en el orden X-, X+, Y+, Y-, Z+, Z-
+    pub fn cube_map(paths: [&str; 6]) -> Self {
+        Background::CubeMap(Box::new(CubeMap::load(paths)))
+    }
+}
+
+// Interpolación bilineal que da la vuelta en longitud (u) y se limita en los polos (v),
+// para que los reflejos del cielo no se vean pixelados ni aparezca una costura.
+fn sample_bilinear_equirectangular(texture: &Texture, u: f32, v: f32) -> Color {
+    let x = u * texture.width as f32 - 0.5;
+    let y = (v * texture.height as f32 - 0.5).clamp(0.0, (texture.height - 1) as f32);
+
+    let x_floor = x.floor();
+    let y_floor = y.floor();
+    let tx = x - x_floor;
+    let ty = y - y_floor;
+
+    let x0 = (x_floor as i64).rem_euclid(texture.width as i64) as usize;
+    let x1 = (x0 + 1) % texture.width;
+    let y0 = y_floor as usize;
+    let y1 = (y0 + 1).min(texture.height - 1);
+
+    let bottom = texture.get_color(x0, y0).lerp(&texture.get_color(x1, y0), tx);
+    let top = texture.get_color(x0, y1).lerp(&texture.get_color(x1, y1), tx);
+    bottom.lerp(&top, ty)
+}
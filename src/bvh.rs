@@ -0,0 +1,226 @@
+use std::any::Any;
+use std::cell::Cell;
+
+use nalgebra_glm::Vec3;
+use crate::intersect::{Intersect, RayIntersect};
+use crate::aabb::Aabb;
+
+// Objetos por hoja: por debajo de esto, probar los objetos directamente sale más barato que otro nivel
+const MAX_LEAF_OBJECTS: usize = 2;
+
+// Desde cuántos objetos los dos hijos de un nodo se construyen en paralelo; por debajo, repartir el
+// trabajo entre hilos cuesta más que ordenar los objetos en el mismo hilo
+const PARALLEL_BUILD_OBJECTS: usize = 4096;
+
+// Profundidad máxima de la pila de recorrido. Al partir por la mediana el árbol queda balanceado, así que
+// alcanza para muchísimos más objetos de los que cabrían en memoria.
+const STACK_SIZE: usize = 64;
+
+// Un objeto con su posición en la lista que recibió `Bvh::new`
+type Child = (usize, Box<dyn RayIntersect>);
+
+// Nodo del árbol guardado en orden de recorrido: el hijo izquierdo de un nodo interno va justo después
+// de él y el derecho `right` posiciones más adelante. Como el desplazamiento es relativo, un subárbol
+// no depende de dónde quede en la lista y los dos hijos se pueden construir por separado. Las hojas
+// tienen `count > 0` objetos a partir de `start`.
+struct Node {
+    bounds: Aabb,
+    start: usize,
+    count: usize,
+    right: usize,
+}
+
+// Jerarquía de volúmenes envolventes: agrupa los objetos por cercanía en cajas anidadas, de modo que un
+// rayo solo prueba los objetos cuyas cajas atraviesa en vez de recorrerlos todos. Como `Group`, para
+// `cast_ray` es una primitiva más. Los objetos sin caja finita (planos) quedan fuera del árbol y se
+// prueban siempre. Si los objetos se mueven, hay que volver a construirla. Cada objeto conserva su
+// posición en la lista original, para que `pick` y el antialiasing por bordes lo distingan de los demás.
+pub struct Bvh {
+    objects: Vec<Child>,  // Ordenados de modo que cada hoja es un rango contiguo
+    unbounded: Vec<Child>,
+    nodes: Vec<Node>,
+}
+
+impl Bvh {
+    pub fn new(objects: Vec<Box<dyn RayIntersect>>) -> Self {
+        let (bounded, unbounded): (Vec<_>, Vec<_>) =
+            objects.into_iter().enumerate().partition(|(_, object)| object.bounding_box().is_finite());
+
+        let mut items: Vec<(Aabb, Child)> =
+            bounded.into_iter().map(|object| (object.1.bounding_box(), object)).collect();
+        let nodes = if items.is_empty() { Vec::new() } else { build(&mut items, 0) };
+
+        Bvh { objects: items.into_iter().map(|(_, object)| object).collect(), unbounded, nodes }
+    }
+
+    // Los objetos en el orden en que se pasaron a `new`
+    pub fn children(&self) -> Vec<&dyn RayIntersect> {
+        let mut children: Vec<_> = self.objects.iter().chain(&self.unbounded).collect();
+        children.sort_by_key(|(id, _)| *id);
+        children.into_iter().map(|(_, object)| object.as_ref()).collect()
+    }
+
+    // Posición en la lista original del objeto más cercano que ve el rayo dentro de [t_min, t_max], con
+    // su intersección
+    pub fn closest_hit(&self, ray_origin: &Vec3, ray_direction: &Vec3, t_min: f32, t_max: f32) -> Option<(usize, Intersect)> {
+        let mut closest = None;
+        // Lo leen tanto la prueba de los objetos como el recorrido, que poda con él
+        let closest_distance = Cell::new(t_max);
+        let mut test = |objects: &[Child]| {
+            for (id, object) in objects {
+                let intersect = object.ray_intersect(ray_origin, ray_direction, t_min, closest_distance.get());
+                if intersect.is_intersecting {
+                    closest_distance.set(intersect.distance);
+                    closest = Some((*id, intersect));
+                }
+            }
+            false
+        };
+
+        test(&self.unbounded);
+        self.traverse(ray_origin, ray_direction, || closest_distance.get(), test);
+        closest
+    }
+
+    pub fn len(&self) -> usize {
+        self.objects.len() + self.unbounded.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    // Recorre en profundidad las hojas cuya caja cruza el rayo antes de `t_max()`, llamando a
+    // `visit` con sus objetos. `t_max` se vuelve a leer en cada nodo para que la búsqueda del impacto
+    // más cercano descarte las cajas que quedan detrás del mejor impacto hasta ahora.
+    fn traverse(&self, ray_origin: &Vec3, ray_direction: &Vec3, t_max: impl Fn() -> f32, mut visit: impl FnMut(&[Child]) -> bool) {
+        if self.nodes.is_empty() {
+            return;
+        }
+        let inv_direction = Vec3::new(1.0 / ray_direction.x, 1.0 / ray_direction.y, 1.0 / ray_direction.z);
+
+        let mut stack = [0usize; STACK_SIZE];
+        let mut len = 1;
+        while len > 0 {
+            len -= 1;
+            let index = stack[len];
+            let node = &self.nodes[index];
+            if !node.bounds.intersects_ray(ray_origin, &inv_direction).is_some_and(|enter| enter <= t_max()) {
+                continue;
+            }
+            if node.count > 0 {
+                if visit(&self.objects[node.start..node.start + node.count]) {
+                    return;
+                }
+            } else {
+                // Se apila primero el derecho para bajar antes por el izquierdo
+                stack[len] = index + node.right;
+                stack[len + 1] = index + 1;
+                len += 2;
+            }
+        }
+    }
+}
+
+// Construye el subárbol de `items` (que empiezan en `start` dentro de la lista final) y devuelve sus
+// nodos, con la raíz primero. Parte por la mediana de los centros a lo largo del eje en que más se
+// extienden. Con muchos objetos y más de un hilo los dos hijos se construyen en paralelo con
+// `rayon::join`; cada uno ordena solo su mitad, así que el árbol es el mismo que en un solo hilo.
+fn build(items: &mut [(Aabb, Child)], start: usize) -> Vec<Node> {
+    let bounds = items.iter().map(|(bounds, _)| *bounds).reduce(|a, b| a.union(&b)).expect("non-empty node");
+    if items.len() <= MAX_LEAF_OBJECTS {
+        return vec![Node { bounds, start, count: items.len(), right: 0 }];
+    }
+
+    let center = |bounds: &Aabb| (bounds.min + bounds.max) * 0.5;
+    let (low, high) = items.iter().fold(
+        (Vec3::repeat(f32::INFINITY), Vec3::repeat(f32::NEG_INFINITY)),
+        |(low, high), (bounds, _)| (low.inf(&center(bounds)), high.sup(&center(bounds))),
+    );
+    let extent = high - low;
+    let axis = if extent.x >= extent.y && extent.x >= extent.z { 0 } else if extent.y >= extent.z { 1 } else { 2 };
+    items.sort_by(|(a, _), (b, _)| center(a)[axis].total_cmp(&center(b)[axis]));
+
+    let middle = items.len() / 2;
+    let parallel = items.len() >= PARALLEL_BUILD_OBJECTS && rayon::current_num_threads() > 1;
+    let (left, right) = items.split_at_mut(middle);
+    let (left, right) = if parallel {
+        rayon::join(|| build(left, start), || build(right, start + middle))
+    } else {
+        (build(left, start), build(right, start + middle))
+    };
+
+    let mut nodes = Vec::with_capacity(1 + left.len() + right.len());
+    nodes.push(Node { bounds, start, count: 0, right: 1 + left.len() });
+    nodes.extend(left);
+    nodes.extend(right);
+    nodes
+}
+
+impl RayIntersect for Bvh {
+    fn ray_intersect(&self, ray_origin: &Vec3, ray_direction: &Vec3, t_min: f32, t_max: f32) -> Intersect {
+        match self.closest_hit(ray_origin, ray_direction, t_min, t_max) {
+            Some((_, intersect)) => intersect,
+            None => Intersect::empty(),
+        }
+    }
+
+    // Igual que en `Group`, los objetos que no proyectan sombra no bloquean
+    fn intersect_any(&self, ray_origin: &Vec3, ray_direction: &Vec3, t_min: f32, t_max: f32) -> Option<f32> {
+        let mut blocker = None;
+        let mut test = |objects: &[Child]| {
+            blocker = objects
+                .iter()
+                .map(|(_, object)| object)
+                .filter(|object| object.casts_shadow())
+                .find_map(|object| object.intersect_any(ray_origin, ray_direction, t_min, t_max));
+            blocker.is_some()
+        };
+
+        if !test(&self.unbounded) {
+            self.traverse(ray_origin, ray_direction, || t_max, test);
+        }
+        blocker
+    }
+
+    // Desde la primera entrada hasta la última salida entre los objetos con volumen
+    fn ray_span(&self, ray_origin: &Vec3, ray_direction: &Vec3) -> Option<(f32, f32)> {
+        self.objects
+            .iter()
+            .chain(&self.unbounded)
+            .filter_map(|(_, object)| object.ray_span(ray_origin, ray_direction))
+            .reduce(|(enter, exit), (object_enter, object_exit)| (enter.min(object_enter), exit.max(object_exit)))
+    }
+
+    fn casts_shadow(&self) -> bool {
+        self.objects.iter().chain(&self.unbounded).any(|(_, object)| object.casts_shadow())
+    }
+
+    // La caja de la raíz, o una no finita si hay objetos infinitos (o ninguno)
+    fn bounding_box(&self) -> Aabb {
+        match self.nodes.first() {
+            Some(root) if self.unbounded.is_empty() => root.bounds,
+            _ => Aabb::new(Vec3::repeat(f32::NEG_INFINITY), Vec3::repeat(f32::INFINITY)),
+        }
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        self
+    }
+}
+
+// Lista plana de los objetos de la escena, con los hijos de cada `Bvh` en su lugar. El índice de cada
+// uno es el id que usan `pick` y `primary_object_id`.
+pub fn flatten(objects: &[Box<dyn RayIntersect>]) -> Vec<&dyn RayIntersect> {
+    objects
+        .iter()
+        .flat_map(|object| match object.as_any().downcast_ref::<Bvh>() {
+            Some(bvh) => bvh.children(),
+            None => vec![object.as_ref()],
+        })
+        .collect()
+}
@@ -1,9 +1,31 @@
 use nalgebra_glm::Vec3;
+use serde::Deserialize;
 
+#[derive(Debug, Clone)]
 pub struct Camera {
     pub eye: Vec3,     // Posición de la cámara en el espacio
     pub center: Vec3,  // Punto en el espacio 3D que la cámara está observando
     pub up: Vec3,      // Vector "arriba"
+    pub fov: f32,      // Campo de visión vertical en grados; el horizontal sale de la relación de aspecto
+    pub lens: Option<Lens>,  // Objetivo y sensor en términos fotográficos; si está, reemplaza a `fov`
+}
+
+// Campo de visión vertical por defecto, en grados
+pub const DEFAULT_FOV: f32 = 90.0;
+
+// Distancia focal y alto del sensor, ambos en milímetros (por ejemplo 50 mm sobre un sensor de 24 mm,
+// el formato completo). El plano de imagen está a `focal_length` del ojo y mide `sensor_height` de alto.
+#[derive(Debug, Clone, Copy)]
+pub struct Lens {
+    pub focal_length: f32,
+    pub sensor_height: f32,
+}
+
+impl Lens {
+    // Campo de visión vertical equivalente, en grados
+    pub fn fov(&self) -> f32 {
+        (2.0 * (self.sensor_height * 0.5 / self.focal_length).atan()).to_degrees()
+    }
 }
 
 impl Camera {
@@ -29,7 +51,33 @@ impl Camera {
         self.center += vertical * self.up;
     }
 
-    
+    // Desplaza la vista en el plano de la pantalla (paneo): eye y center se mueven juntos `dx` a lo largo
+    // de la derecha de la cámara y `dy` a lo largo de su arriba real (no el del mundo), sin cambiar
+    // hacia dónde mira
+    pub fn pan(&mut self, dx: f32, dy: f32) {
+        let forward = (self.center - self.eye).normalize();
+        let right = forward.cross(&self.up).normalize();
+        let up = right.cross(&forward).normalize();
+
+        let offset = dx * right + dy * up;
+        self.eye += offset;
+        self.center += offset;
+    }
+
+    // Mitad de la altura de la imagen en el plano a distancia 1 del ojo: tan(fov / 2), o con un objetivo,
+    // la mitad del sensor escalada por la distancia focal
+    pub fn half_height(&self) -> f32 {
+        match self.lens {
+            Some(lens) => lens.sensor_height * 0.5 / lens.focal_length,
+            None => (self.fov.to_radians() * 0.5).tan(),
+        }
+    }
+
+    // Campo de visión vertical que produce la cámara, en grados, venga de `fov` o del objetivo
+    pub fn vertical_fov(&self) -> f32 {
+        self.lens.map_or(self.fov, |lens| lens.fov())
+    }
+
     // Cambiar la base para transformar un vector usando los vectores right, up y forward
     pub fn basis_change(&self, vector: &Vec3) -> Vec3 {
         let forward = (self.center - self.eye).normalize();
@@ -44,6 +92,30 @@ impl Camera {
         rotated.normalize()
     }
 
+    // Proyecta un punto del mundo a coordenadas de píxel; None si queda detrás de la cámara.
+    // Es la inversa de la generación de rayos de `render`.
+    pub fn project(&self, point: &Vec3, width: usize, height: usize) -> Option<(f32, f32)> {
+        let forward = (self.center - self.eye).normalize();
+        let right = forward.cross(&self.up).normalize();
+        let up = right.cross(&forward).normalize();
+
+        let relative = point - self.eye;
+        let depth = relative.dot(&forward);
+        if depth <= 1e-3 {
+            return None;
+        }
+
+        let half_height = self.half_height();
+        let half_width = half_height * width as f32 / height as f32;
+        let screen_x = relative.dot(&right) / depth / half_width;
+        let screen_y = relative.dot(&up) / depth / half_height;
+
+        Some((
+            (screen_x + 1.0) * 0.5 * width as f32,
+            (1.0 - screen_y) * 0.5 * height as f32,
+        ))
+    }
+
     // Método para realizar la órbita de la cámara en base a los cambios en yaw y pitch
     pub fn orbit(&mut self, delta_yaw: f32, delta_pitch: f32) {
         // Calcular el vector desde el center hacia el eye (vector del radio) y medir la distancia
@@ -72,3 +144,54 @@ impl Camera {
         self.eye = new_eye;
     }
 }
+
+// Valores de la cámara leídos de un archivo TOML; los que falten se toman de la cámara por defecto.
+// Ejemplo:
+//   eye = [8.0, 12.0, -25.0]
+//   center = [0.0, 0.0, -1.0]
+//   fov = 60.0
+// En lugar de `fov` se puede dar `focal_length` y `sensor_height` en milímetros; sin `sensor_height`
+// se asume un sensor de formato completo (24 mm).
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct CameraConfig {
+    eye: Option<[f32; 3]>,
+    center: Option<[f32; 3]>,
+    up: Option<[f32; 3]>,
+    fov: Option<f32>,
+    focal_length: Option<f32>,
+    sensor_height: Option<f32>,
+}
+
+// Alto del sensor de formato completo (35 mm), en milímetros
+pub const FULL_FRAME_SENSOR_HEIGHT: f32 = 24.0;
+
+// Carga la cámara desde `path`. Si el archivo no existe se usa `default` tal cual; si existe pero no
+// se puede leer o tiene errores, se devuelve el error para que quien llama decida qué hacer.
+pub fn load_camera(path: &str, default: Camera) -> Result<Camera, String> {
+    let text = match std::fs::read_to_string(path) {
+        Ok(text) => text,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(default),
+        Err(e) => return Err(format!("{}: {}", path, e)),
+    };
+    let config: CameraConfig = toml::from_str(&text).map_err(|e| format!("{}: {}", path, e))?;
+
+    let lens = match (config.focal_length, config.sensor_height) {
+        (Some(focal_length), sensor_height) => Some(Lens {
+            focal_length,
+            sensor_height: sensor_height.unwrap_or(FULL_FRAME_SENSOR_HEIGHT),
+        }),
+        (None, Some(_)) => return Err(format!("{}: sensor_height requiere focal_length", path)),
+        (None, None) if config.fov.is_some() => None,
+        (None, None) => default.lens,
+    };
+
+    let vector = |value: Option<[f32; 3]>, fallback: Vec3| value.map_or(fallback, |[x, y, z]| Vec3::new(x, y, z));
+    Ok(Camera {
+        eye: vector(config.eye, default.eye),
+        center: vector(config.center, default.center),
+        up: vector(config.up, default.up),
+        fov: config.fov.unwrap_or(default.fov),
+        lens,
+    })
+}
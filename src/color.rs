@@ -1,4 +1,4 @@
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, Default)]
 pub struct Color {
     pub r: u8,
     pub g: u8,
@@ -9,4 +9,59 @@ impl Color {
     pub fn new(r: u8, g: u8, b: u8) -> Self {
         Color { r, g, b }
     }
+
+    // Color a partir de canales en [0, 255] calculados en punto flotante. Se recortan al rango y los
+    // valores no finitos (NaN o infinito, por ejemplo de normalizar un vector nulo) quedan en 0 en
+    // vez de convertirse en puntos brillantes sueltos
+    pub fn from_f32(r: f32, g: f32, b: f32) -> Self {
+        let channel = |value: f32| if value.is_finite() { value.clamp(0.0, 255.0) as u8 } else { 0 };
+        Color::new(channel(r), channel(g), channel(b))
+    }
+
+    // Color a partir de canales en [0, 1]; se recortan al rango y se redondean al byte más cercano, así
+    // que `from_normalized(c.rf(), c.gf(), c.bf())` devuelve `c`. NaN queda en 0.
+    pub fn from_normalized(r: f32, g: f32, b: f32) -> Self {
+        let channel = |value: f32| if value.is_nan() { 0 } else { (value.clamp(0.0, 1.0) * 255.0).round() as u8 };
+        Color::new(channel(r), channel(g), channel(b))
+    }
+
+    // Canales como f32 en [0, 1]
+    pub fn rf(&self) -> f32 {
+        self.r as f32 / 255.0
+    }
+
+    pub fn gf(&self) -> f32 {
+        self.g as f32 / 255.0
+    }
+
+    pub fn bf(&self) -> f32 {
+        self.b as f32 / 255.0
+    }
+
+    // Luminancia relativa con los pesos de Rec. 709, en el rango [0, 1]
+    pub fn luminance(&self) -> f32 {
+        0.2126 * self.rf() + 0.7152 * self.gf() + 0.0722 * self.bf()
+    }
+
+    // El mismo color sin saturación, conservando su luminancia
+    pub fn grayscale(&self) -> Color {
+        let value = (self.luminance() * 255.0).round() as u8;
+        Color::new(value, value, value)
+    }
+
+    // Producto canal a canal, con 255 como 1: multiplicar por blanco deja el color igual
+    pub fn multiply(&self, other: &Color) -> Color {
+        let channel = |a: u8, b: u8| ((a as u32 * b as u32 + 127) / 255) as u8;
+        Color { r: channel(self.r, other.r), g: channel(self.g, other.g), b: channel(self.b, other.b) }
+    }
+
+    // Interpolación lineal hacia `other`; t = 0 devuelve self y t = 1 devuelve other
+    pub fn lerp(&self, other: &Color, t: f32) -> Color {
+        let t = t.clamp(0.0, 1.0);
+        Color {
+            r: (self.r as f32 + (other.r as f32 - self.r as f32) * t).round() as u8,
+            g: (self.g as f32 + (other.g as f32 - self.g as f32) * t).round() as u8,
+            b: (self.b as f32 + (other.b as f32 - self.b as f32) * t).round() as u8,
+        }
+    }
 }
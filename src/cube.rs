@@ -3,16 +3,73 @@ use std::any::Any;
 use nalgebra_glm::Vec3;
 use crate::intersect::{Intersect, RayIntersect}; // Cambiado de ray_intersect a intersect
 use crate::material::Material; // Cambiado de ray_intersect a material
+use crate::aabb::Aabb;
 
+// `center` y `size` son privados porque las esquinas se precalculan a partir de ellos: solo se
+// cambian con `set_center` y `set_size`, que las actualizan
 pub struct Cube {
-    pub center: Vec3,
-    pub size: f32,
+    center: Vec3,
+    size: f32,
     pub materials: [Material; 6], 
     pub original_center: Vec3,  // Nuevo campo para almacenar la posición original  
     pub is_water: bool,         // Nuevo campo para identificar si es un cubo de agua
+    pub casts_shadow: bool,     // false para geometría auxiliar o decorativa que no debe dar sombra
+    min: Vec3,                  // Esquina mínima, derivada de center y size
+    max: Vec3,                  // Esquina máxima, derivada de center y size
 }
 
 impl Cube {
+    // Crea un cubo en su posición original; `materials` sigue el orden de caras X-, X+, Y+, Y-, Z+, Z-
+    pub fn new(center: Vec3, size: f32, materials: [Material; 6], is_water: bool) -> Self {
+        Cube {
+            center,
+            size,
+            materials,
+            original_center: center,
+            is_water,
+            casts_shadow: true,
+            min: center - Vec3::repeat(size / 2.0),
+            max: center + Vec3::repeat(size / 2.0),
+        }
+    }
+
+    pub fn center(&self) -> Vec3 {
+        self.center
+    }
+
+    pub fn size(&self) -> f32 {
+        self.size
+    }
+
+    // Mueve el cubo y actualiza sus esquinas precalculadas
+    pub fn set_center(&mut self, center: Vec3) {
+        self.center = center;
+        self.update_corners();
+    }
+
+    // Cambia el lado del cubo, sin moverlo, y actualiza sus esquinas precalculadas
+    pub fn set_size(&mut self, size: f32) {
+        self.size = size;
+        self.update_corners();
+    }
+
+    fn update_corners(&mut self) {
+        self.min = self.center - Vec3::repeat(self.size / 2.0);
+        self.max = self.center + Vec3::repeat(self.size / 2.0);
+    }
+
+    // Cubo con el mismo material en las seis caras (el caso más común)
+    pub fn uniform(center: Vec3, size: f32, material: Material) -> Self {
+        let materials = [
+            material.clone(),
+            material.clone(),
+            material.clone(),
+            material.clone(),
+            material.clone(),
+            material,
+        ];
+        Cube::new(center, size, materials, false)
+    }
 
     pub fn get_uv_for_face(face_index: usize, local_pos: Vec3) -> (f32, f32) {
         match face_index {
@@ -33,6 +90,12 @@ impl Cube {
     }
     
 
+    // Distancias donde el rayo entra (t1) y sale (t2) de las tres franjas del cubo; no hay cruce si t1 > t2
+    fn slab_interval(&self, ray_origin: &Vec3, ray_direction: &Vec3) -> (f32, f32) {
+        let inv_dir = Vec3::new(1.0 / ray_direction.x, 1.0 / ray_direction.y, 1.0 / ray_direction.z);
+        self.bounding_box().slab_interval(ray_origin, &inv_dir)
+    }
+
     // Este método mapea las coordenadas UV en el rango [0, 1].
     fn map_uv(u: f32, v: f32) -> (f32, f32) {
         let u = (u + 1.0) * 0.5;
@@ -44,23 +107,22 @@ impl Cube {
         
 
 impl RayIntersect for Cube {
-    fn ray_intersect(&self, ray_origin: &Vec3, ray_direction: &Vec3) -> Intersect {
-        let mitad = self.size / 2.0;
-        let min = self.center - Vec3::new(mitad, mitad, mitad);
-        let max = self.center + Vec3::new(mitad, mitad, mitad);
-
-        let inv_dir = Vec3::new(1.0 / ray_direction.x, 1.0 / ray_direction.y, 1.0 / ray_direction.z);
-        let t_min = (min - ray_origin).component_mul(&inv_dir);
-        let t_max = (max - ray_origin).component_mul(&inv_dir);
-
-        let t1 = t_min.x.min(t_max.x).max(t_min.y.min(t_max.y)).max(t_min.z.min(t_max.z));
-        let t2 = t_min.x.max(t_max.x).min(t_min.y.max(t_max.y)).min(t_min.z.max(t_max.z));
+    fn ray_intersect(&self, ray_origin: &Vec3, ray_direction: &Vec3, t_min: f32, t_max: f32) -> Intersect {
+        let (min, max) = (self.min, self.max);
+        let (t1, t2) = self.slab_interval(ray_origin, ray_direction);
 
-        if t1 > t2 || t2 < 0.0 {
+        if t1 > t2 {
             return Intersect::empty();
         }
 
-        let t_hit = if t1 < 0.0 { t2 } else { t1 };
+        // Tomamos la entrada si está en el rango; si no, la salida (el rayo empieza dentro del cubo)
+        let t_hit = if t1 >= t_min && t1 <= t_max {
+            t1
+        } else if t2 >= t_min && t2 <= t_max {
+            t2
+        } else {
+            return Intersect::empty();
+        };
         let punto_encuentro = ray_origin + ray_direction * t_hit;
 
         let mut normal = Vec3::new(0.0, 0.0, 0.0);
@@ -96,8 +158,39 @@ impl RayIntersect for Cube {
             t_hit,
             self.materials[face_index].clone(),
             u,
-            v
+            v,
+            t_hit == t1,  // Si el impacto es la entrada al cubo, el rayo viene de afuera
         )
+        .with_uv_scale(0.5)  // `map_uv` repite la textura cada 2 unidades, el lado de los cubos de la escena
+    }
+
+    fn intersect_any(&self, ray_origin: &Vec3, ray_direction: &Vec3, t_min: f32, t_max: f32) -> Option<f32> {
+        let (t1, t2) = self.slab_interval(ray_origin, ray_direction);
+        if t1 > t2 {
+            return None;
+        }
+        [t1, t2].into_iter().find(|t| *t >= t_min && *t <= t_max)
+    }
+
+    fn ray_span(&self, ray_origin: &Vec3, ray_direction: &Vec3) -> Option<(f32, f32)> {
+        let (t1, t2) = self.slab_interval(ray_origin, ray_direction);
+        if t1 > t2 || t2 < 0.0 {
+            return None;
+        }
+        // Si el rayo empieza dentro del cubo, la entrada es el propio origen
+        Some((t1.max(0.0), t2))
+    }
+
+    fn casts_shadow(&self) -> bool {
+        self.casts_shadow
+    }
+
+    fn bounding_box(&self) -> Aabb {
+        Aabb::new(self.min, self.max)
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
     }
 
     fn as_any_mut(&mut self) -> &mut dyn Any {
@@ -0,0 +1,88 @@
+use rayon::prelude::*;
+
+use crate::gbuffer::GBuffer;
+use crate::render::{in_thread_pool, RenderConfig};
+
+// Pesos del núcleo B3-spline de 5 taps usado por el filtro à-trous, para desplazamientos 0, ±1 y ±2
+const KERNEL: [f32; 3] = [3.0 / 8.0, 1.0 / 4.0, 1.0 / 16.0];
+
+// Tolerancias de los "edge-stopping": cuánto pueden diferir color, normal y profundidad de dos
+// píxeles antes de que dejen de mezclarse
+const SIGMA_COLOR: f32 = 0.25;
+const NORMAL_POWER: i32 = 64;
+const SIGMA_DEPTH: f32 = 0.5;
+
+// Filtro à-trous guiado por el G-buffer: cada iteración promedia con vecinos cada vez más lejanos
+// (pasos 1, 2, 4, ...) sin cruzar bordes de geometría ni cambios fuertes de color.
+// La intensidad es `config.denoise_iterations`; 0 deja el framebuffer intacto. El byte alto (alfa) se
+// conserva. Corre en el pool de hilos de `config`, como el render.
+pub fn denoise_atrous(framebuffer: &mut [u32], gbuffer: &GBuffer, config: &RenderConfig) {
+    let (width, height) = (gbuffer.width, gbuffer.height);
+    let iterations = config.denoise_iterations;
+    let mut colors: Vec<[f32; 3]> = framebuffer
+        .iter()
+        .map(|pixel| [(pixel >> 16) & 0xFF, (pixel >> 8) & 0xFF, pixel & 0xFF].map(|c| c as f32 / 255.0))
+        .collect();
+
+    for iteration in 0..iterations {
+        let step = 1i64 << iteration;
+        // Cada iteración es más estricta con el color, porque el ruido que queda es menor
+        let sigma_color = SIGMA_COLOR / (1 << iteration) as f32;
+
+        colors = in_thread_pool(config, || (0..width * height)
+            .into_par_iter()
+            .map(|index| {
+                let (x, y) = ((index % width) as i64, (index / width) as i64);
+                let color = colors[index];
+                let mut sum = [0.0f32; 3];
+                let mut weight_sum = 0.0;
+
+                for dy in -2i64..=2 {
+                    for dx in -2i64..=2 {
+                        let (qx, qy) = (x + dx * step, y + dy * step);
+                        if qx < 0 || qy < 0 || qx >= width as i64 || qy >= height as i64 {
+                            continue;
+                        }
+                        let neighbor = qy as usize * width + qx as usize;
+
+                        let weight = KERNEL[dx.unsigned_abs() as usize]
+                            * KERNEL[dy.unsigned_abs() as usize]
+                            * edge_weight(gbuffer, index, neighbor, step as f32)
+                            * color_weight(&color, &colors[neighbor], sigma_color);
+
+                        for c in 0..3 {
+                            sum[c] += colors[neighbor][c] * weight;
+                        }
+                        weight_sum += weight;
+                    }
+                }
+
+                // El propio píxel siempre tiene peso positivo, así que weight_sum > 0
+                sum.map(|channel| channel / weight_sum)
+            })
+            .collect());
+    }
+
+    for (pixel, color) in framebuffer.iter_mut().zip(&colors) {
+        let [r, g, b] = color.map(|c| (c.clamp(0.0, 1.0) * 255.0).round() as u32);
+        *pixel = (*pixel & 0xFF00_0000) | (r << 16) | (g << 8) | b;
+    }
+}
+
+// Peso geométrico: normales parecidas y profundidades cercanas; el fondo solo se mezcla con fondo
+fn edge_weight(gbuffer: &GBuffer, a: usize, b: usize, step: f32) -> f32 {
+    match (gbuffer.is_background(a), gbuffer.is_background(b)) {
+        (true, true) => 1.0,
+        (false, false) => {
+            let normal = gbuffer.normals[a].dot(&gbuffer.normals[b]).max(0.0).powi(NORMAL_POWER);
+            let depth = (-(gbuffer.depths[a] - gbuffer.depths[b]).abs() / (SIGMA_DEPTH * step)).exp();
+            normal * depth
+        }
+        _ => 0.0,
+    }
+}
+
+fn color_weight(a: &[f32; 3], b: &[f32; 3], sigma: f32) -> f32 {
+    let distance_sq: f32 = (0..3).map(|c| (a[c] - b[c]) * (a[c] - b[c])).sum();
+    (-distance_sq / (sigma * sigma)).exp()
+}
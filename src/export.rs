@@ -0,0 +1,94 @@
+// Guardado de framebuffers en disco
+
+use nalgebra_glm::Vec3;
+
+use crate::camera::{Camera, DEFAULT_FOV};
+use crate::render::{render, Projection, RenderConfig};
+use crate::scene::Scene;
+
+// Guarda un framebuffer ARGB (como el que produce `render` con `alpha_matte`) como PNG RGBA,
+// para componer el render sobre otra imagen
+pub fn save_rgba_png(path: &str, framebuffer: &[u32], width: usize, height: usize) -> image::ImageResult<()> {
+    let data: Vec<u8> = framebuffer
+        .iter()
+        .flat_map(|pixel| [(pixel >> 16) as u8, (pixel >> 8) as u8, *pixel as u8, (pixel >> 24) as u8])
+        .collect();
+
+    image::save_buffer(path, &data, width as u32, height as u32, image::ColorType::Rgba8)
+}
+
+
+// Guarda un framebuffer 0RGB como PNG RGB
+pub fn save_png(path: &str, framebuffer: &[u32], width: usize, height: usize) -> image::ImageResult<()> {
+    let data: Vec<u8> = framebuffer
+        .iter()
+        .flat_map(|pixel| [(pixel >> 16) as u8, (pixel >> 8) as u8, *pixel as u8])
+        .collect();
+
+    image::save_buffer(path, &data, width as u32, height as u32, image::ColorType::Rgb8)
+}
+
+
+// Guarda un framebuffer 0RGB como PPM binario (P6): una cabecera de texto seguida de los bytes RGB
+// de cada píxel, sin compresión. Es un formato trivial de leer y comparar desde scripts o pruebas.
+pub fn save_ppm(path: &str, framebuffer: &[u32], width: usize, height: usize) -> std::io::Result<()> {
+    let mut data = format!("P6\n{} {}\n255\n", width, height).into_bytes();
+    data.extend(framebuffer.iter().flat_map(|pixel| [(pixel >> 16) as u8, (pixel >> 8) as u8, *pixel as u8]));
+
+    std::fs::write(path, data)
+}
+
+
+// Dibuja un histograma (por ejemplo el de `luminance_histogram`) como barras blancas sobre negro,
+// con la altura de cada barra relativa al intervalo más lleno
+pub fn save_histogram_png(path: &str, histogram: &[u32], width: usize, height: usize) -> image::ImageResult<()> {
+    let largest = histogram.iter().copied().max().unwrap_or(0).max(1) as f32;
+    let mut data = vec![0u8; width * height];
+    for x in 0..width {
+        let count = histogram.get(x * histogram.len() / width).copied().unwrap_or(0);
+        let bar_height = (count as f32 / largest * height as f32).round() as usize;
+        for y in height - bar_height.min(height)..height {
+            data[y * width + x] = 255;
+        }
+    }
+
+    image::save_buffer(path, &data, width as u32, height as u32, image::ColorType::L8)
+}
+
+
+// Renderiza la escena completa vista desde arriba con proyección ortográfica, encuadrada con los
+// límites de la escena, y la guarda como un mapa en PNG. El norte (-Z) queda arriba.
+pub fn export_top_down_map(
+    path: &str,
+    scene: &Scene,
+    config: &RenderConfig,
+    size: (usize, usize),
+) -> image::ImageResult<()> {
+    let (width, height) = size;
+    let (min, max) = scene.bounds();
+
+    let middle = (min + max) * 0.5;
+    let camera = Camera {
+        eye: Vec3::new(middle.x, max.y + 1.0, middle.z),
+        center: Vec3::new(middle.x, min.y, middle.z),
+        up: Vec3::new(0.0, 0.0, -1.0),
+        fov: DEFAULT_FOV,
+        lens: None,
+    };
+
+    // La altura visible cubre la profundidad de la escena y el ancho según la relación de aspecto, con margen
+    let aspect_ratio = width as f32 / height as f32;
+    let view_height = (max.z - min.z).max((max.x - min.x) / aspect_ratio) * 1.05;
+
+    let map_config = RenderConfig {
+        projection: Projection::Orthographic { view_height },
+        region: None,
+        time_budget: None,
+        alpha_matte: false,
+        ..config.clone()
+    };
+
+    let mut framebuffer = vec![0; width * height];
+    render(&mut framebuffer, width, height, &scene.objects, &camera, &scene.lights, &map_config);
+    save_png(path, &framebuffer, width, height)
+}
@@ -0,0 +1,93 @@
+use crate::color::Color;
+
+// Exposición automática amortiguada. Empieza desactivada: mide el cuadro anterior ya cuantizado a 8
+// bits y recortado por el mapeo de tonos, no el color en punto flotante, así que una zona quemada
+// cuenta como 1 por brillante que sea y los negros profundos se pierden. En escenas de mucho contraste
+// la exposición se queda corta; hasta medir un buffer HDR, eso es una limitación conocida.
+pub struct AutoExposure {
+    pub enabled: bool,
+    pub exposure: f32,  // Multiplicador aplicado al color final
+    pub target: f32,    // Luminancia promedio deseada en el rango [0, 1]
+    pub speed: f32,     // Fracción del ajuste aplicada por cuadro (amortiguación)
+}
+
+impl AutoExposure {
+    pub fn new(target: f32, speed: f32) -> Self {
+        AutoExposure {
+            enabled: false,
+            exposure: 1.0,
+            target,
+            speed,
+        }
+    }
+
+    // Ajusta la exposición a partir del cuadro anterior, que ya fue renderizado con la exposición actual
+    pub fn update(&mut self, framebuffer: &[u32]) {
+        if !self.enabled {
+            return;
+        }
+
+        let average = log_average_luminance(framebuffer);
+        let desired = (self.exposure * self.target / average).clamp(0.25, 4.0);
+
+        // Nos acercamos gradualmente al valor deseado para evitar parpadeos
+        self.exposure += (desired - self.exposure) * self.speed;
+    }
+}
+
+// Color y luminancia de un píxel 0RGB
+fn pixel_luminance(pixel: u32) -> f32 {
+    Color::new((pixel >> 16) as u8, (pixel >> 8) as u8, pixel as u8).luminance()
+}
+
+// Luminancia promedio logarítmica del framebuffer, con canales normalizados a [0, 1]
+pub fn log_average_luminance(framebuffer: &[u32]) -> f32 {
+    if framebuffer.is_empty() {
+        return 1.0;
+    }
+
+    let delta = 1e-4;  // Evita ln(0) en píxeles negros
+    let sum: f32 = framebuffer
+        .iter()
+        .map(|pixel| (delta + pixel_luminance(*pixel)).ln())
+        .sum();
+
+    (sum / framebuffer.len() as f32).exp()
+}
+
+
+// Cantidad de píxeles por intervalo de luminancia: `bins` intervalos iguales en [0, 1]. Un primer
+// intervalo muy lleno indica negros aplastados y un último muy lleno, luces recortadas.
+pub fn luminance_histogram(framebuffer: &[u32], bins: usize) -> Vec<u32> {
+    let mut histogram = vec![0; bins.max(1)];
+    let last = histogram.len() - 1;
+    for pixel in framebuffer {
+        let bin = ((pixel_luminance(*pixel) * histogram.len() as f32) as usize).min(last);
+        histogram[bin] += 1;
+    }
+    histogram
+}
+
+// Histograma como texto, una línea por intervalo con su rango, una barra y el porcentaje de píxeles
+pub fn histogram_text(histogram: &[u32]) -> String {
+    const BAR_WIDTH: usize = 40;
+    let total = histogram.iter().sum::<u32>().max(1) as f32;
+    let largest = histogram.iter().copied().max().unwrap_or(0).max(1) as f32;
+    let bins = histogram.len() as f32;
+
+    histogram
+        .iter()
+        .enumerate()
+        .map(|(i, count)| {
+            let bar = "#".repeat((*count as f32 / largest * BAR_WIDTH as f32).round() as usize);
+            format!(
+                "{:.3}-{:.3} {:<width$} {:5.1}%\n",
+                i as f32 / bins,
+                (i + 1) as f32 / bins,
+                bar,
+                *count as f32 / total * 100.0,
+                width = BAR_WIDTH
+            )
+        })
+        .collect()
+}
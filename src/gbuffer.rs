@@ -0,0 +1,117 @@
+use nalgebra_glm::Vec3;
+use rayon::prelude::*;
+
+use crate::camera::Camera;
+use crate::intersect::{Intersect, RayIntersect};
+use crate::render::{in_thread_pool, primary_ray, RenderConfig, RAY_EPSILON};
+
+// Datos geométricos por píxel del impacto primario: normal y profundidad (distancia desde la cámara).
+// Los píxeles que solo ven el fondo tienen normal cero y profundidad infinita.
+pub struct GBuffer {
+    pub width: usize,
+    pub height: usize,
+    pub normals: Vec<Vec3>,
+    pub depths: Vec<f32>,
+}
+
+// Cuánto pueden diferir dos muestras vecinas (coseno entre normales y diferencia de profundidad relativa)
+// para que `render_guided` las tome como la misma superficie y no lance rayos entre ellas
+const SAME_SURFACE_NORMAL: f32 = 0.95;
+const SAME_SURFACE_DEPTH: f32 = 0.05;
+
+impl GBuffer {
+    // Lanza un rayo primario por píxel (en la misma posición que `render` con una muestra) sin sombrear
+    pub fn render(
+        width: usize,
+        height: usize,
+        objects: &[Box<dyn RayIntersect>],
+        camera: &Camera,
+        config: &RenderConfig,
+    ) -> Self {
+        let (normals, depths) = in_thread_pool(config, || (0..width * height)
+            .into_par_iter()
+            .map(|i| primary_hit(i, width, height, objects, camera, config))
+            .unzip());
+
+        GBuffer { width, height, normals, depths }
+    }
+
+    // G-buffer de `width` x `height` para escalar desde `low` con `upscale_edge_aware`. Solo lanza rayos
+    // en los píxeles cuyas 4 muestras de baja resolución no son todas de la misma superficie; los demás
+    // copian la muestra más cercana, porque entre muestras parecidas el escalado guiado ya es un
+    // bilineal. Los bordes son una fracción pequeña de la imagen, así que cuesta mucho menos que `render`.
+    pub fn render_guided(
+        low: &GBuffer,
+        width: usize,
+        height: usize,
+        objects: &[Box<dyn RayIntersect>],
+        camera: &Camera,
+        config: &RenderConfig,
+    ) -> Self {
+        let (normals, depths) = in_thread_pool(config, || (0..width * height)
+            .into_par_iter()
+            .map(|i| {
+                // La misma correspondencia entre píxeles y muestras que usa `upscale_edge_aware`
+                let low_x = (i % width) as f32 * low.width as f32 / width as f32;
+                let low_y = (i / width) as f32 * low.height as f32 / height as f32;
+                let (x0, y0) = (low_x.floor() as usize, low_y.floor() as usize);
+                let sample = |dx: usize, dy: usize| (y0 + dy).min(low.height - 1) * low.width + (x0 + dx).min(low.width - 1);
+
+                let first = sample(0, 0);
+                if [sample(1, 0), sample(0, 1), sample(1, 1)].into_iter().all(|other| low.same_surface(first, other)) {
+                    let nearest = sample(low_x.fract().round() as usize, low_y.fract().round() as usize);
+                    (low.normals[nearest], low.depths[nearest])
+                } else {
+                    primary_hit(i, width, height, objects, camera, config)
+                }
+            })
+            .unzip());
+
+        GBuffer { width, height, normals, depths }
+    }
+
+    pub fn is_background(&self, index: usize) -> bool {
+        self.depths[index].is_infinite()
+    }
+
+    // Si las muestras `a` y `b` son fondo las dos, o de una superficie con casi la misma normal y profundidad
+    fn same_surface(&self, a: usize, b: usize) -> bool {
+        match (self.is_background(a), self.is_background(b)) {
+            (true, true) => true,
+            (false, false) => {
+                let depth = self.depths[a];
+                self.normals[a].dot(&self.normals[b]) >= SAME_SURFACE_NORMAL
+                    && (depth - self.depths[b]).abs() <= SAME_SURFACE_DEPTH * depth
+            }
+            _ => false,
+        }
+    }
+}
+
+// Normal y profundidad del impacto primario del píxel `i`; normal cero y profundidad infinita si no hay
+fn primary_hit(
+    i: usize,
+    width: usize,
+    height: usize,
+    objects: &[Box<dyn RayIntersect>],
+    camera: &Camera,
+    config: &RenderConfig,
+) -> (Vec3, f32) {
+    let (ray_origin, ray_direction) = primary_ray(camera, config.projection, (i % width) as f32, (i / width) as f32, width, height);
+
+    let mut closest = Intersect::empty();
+    let mut closest_distance = f32::INFINITY;
+    for object in objects {
+        let intersection = object.ray_intersect(&ray_origin, &ray_direction, RAY_EPSILON, closest_distance);
+        if intersection.is_intersecting {
+            closest_distance = intersection.distance;
+            closest = intersection;
+        }
+    }
+
+    if closest.is_intersecting {
+        (closest.normal, closest.distance)
+    } else {
+        (Vec3::zeros(), f32::INFINITY)
+    }
+}
@@ -0,0 +1,68 @@
+use std::any::Any;
+
+use nalgebra_glm::Vec3;
+use crate::intersect::{Intersect, RayIntersect};
+use crate::aabb::Aabb;
+
+// Varios objetos tratados como uno solo (por ejemplo un árbol: tronco y hojas). Para `cast_ray` es
+// una primitiva más, cuyo impacto es el del hijo más cercano.
+pub struct Group {
+    pub children: Vec<Box<dyn RayIntersect>>,
+}
+
+impl Group {
+    pub fn new(children: Vec<Box<dyn RayIntersect>>) -> Self {
+        Group { children }
+    }
+}
+
+impl RayIntersect for Group {
+    fn ray_intersect(&self, ray_origin: &Vec3, ray_direction: &Vec3, t_min: f32, t_max: f32) -> Intersect {
+        let mut closest = Intersect::empty();
+        let mut closest_distance = t_max;
+        for child in &self.children {
+            let intersect = child.ray_intersect(ray_origin, ray_direction, t_min, closest_distance);
+            if intersect.is_intersecting {
+                closest_distance = intersect.distance;
+                closest = intersect;
+            }
+        }
+        closest
+    }
+
+    // Igual que en `cast_shadow`, los hijos que no proyectan sombra no bloquean
+    fn intersect_any(&self, ray_origin: &Vec3, ray_direction: &Vec3, t_min: f32, t_max: f32) -> Option<f32> {
+        self.children
+            .iter()
+            .filter(|child| child.casts_shadow())
+            .find_map(|child| child.intersect_any(ray_origin, ray_direction, t_min, t_max))
+    }
+
+    // Desde la primera entrada hasta la última salida entre los hijos con volumen
+    fn ray_span(&self, ray_origin: &Vec3, ray_direction: &Vec3) -> Option<(f32, f32)> {
+        self.children
+            .iter()
+            .filter_map(|child| child.ray_span(ray_origin, ray_direction))
+            .reduce(|(enter, exit), (child_enter, child_exit)| (enter.min(child_enter), exit.max(child_exit)))
+    }
+
+    fn casts_shadow(&self) -> bool {
+        self.children.iter().any(|child| child.casts_shadow())
+    }
+
+    // Unión de las cajas de los hijos; un grupo vacío da una caja no finita, que se ignora como la de un plano
+    fn bounding_box(&self) -> Aabb {
+        self.children.iter().map(|child| child.bounding_box()).fold(
+            Aabb::new(Vec3::repeat(f32::INFINITY), Vec3::repeat(f32::NEG_INFINITY)),
+            |bounds, child| bounds.union(&child),
+        )
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        self
+    }
+}
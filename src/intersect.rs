@@ -1,7 +1,9 @@
 use std::any::Any;
+use std::sync::Arc;
 
 use nalgebra_glm::Vec3;
 use crate::material::Material;
+use crate::aabb::Aabb;
 
 #[derive(Debug, Clone)]
 #[allow(dead_code)]
@@ -13,10 +15,13 @@ pub struct Intersect {
     pub material: Material,
     pub u: f32,  // Coordenada U
     pub v: f32,  // Coordenada V
+    pub front_face: bool,  // true si el rayo llega desde afuera (entra al objeto), false si sale
+    pub uv_scale: f32,  // Unidades de UV por unidad de mundo cerca del impacto, para pasar el ancho del rayo a la textura
 }
 
 impl Intersect {
-    pub fn new(point: Vec3, normal: Vec3, distance: f32, material: Material, u: f32, v: f32) -> Self {
+    // `normal` es la normal geométrica hacia afuera, sin importar de qué lado llegue el rayo
+    pub fn new(point: Vec3, normal: Vec3, distance: f32, material: Material, u: f32, v: f32, front_face: bool) -> Self {
         Intersect {
             point,
             normal,
@@ -25,9 +30,16 @@ impl Intersect {
             material,
             u,
             v,
+            front_face,
+            uv_scale: 1.0,
         }
     }
 
+    // La misma intersección con otra densidad de UV; por defecto se supone una unidad de UV por unidad de mundo
+    pub fn with_uv_scale(self, uv_scale: f32) -> Self {
+        Intersect { uv_scale, ..self }
+    }
+
     pub fn empty() -> Self {
         Intersect {
             point: Vec3::zeros(),
@@ -37,11 +49,68 @@ impl Intersect {
             material: Material::black(),
             u: 0.0,
             v: 0.0,
+            front_face: false,
+            uv_scale: 1.0,
         }
     }
 }
 
-pub trait RayIntersect: Sync {
-    fn ray_intersect(&self, ray_origin: &Vec3, ray_direction: &Vec3) -> Intersect;
+pub trait RayIntersect: Send + Sync {
+    // Solo se aceptan impactos con distancia dentro de [t_min, t_max]
+    fn ray_intersect(&self, ray_origin: &Vec3, ray_direction: &Vec3, t_min: f32, t_max: f32) -> Intersect;
+
+    // Distancia de algún impacto dentro de [t_min, t_max], sin calcular normal, material ni UV.
+    // Basta para las consultas de sombra, que solo necesitan saber si algo bloquea y a qué distancia.
+    fn intersect_any(&self, ray_origin: &Vec3, ray_direction: &Vec3, t_min: f32, t_max: f32) -> Option<f32> {
+        let intersect = self.ray_intersect(ray_origin, ray_direction, t_min, t_max);
+        intersect.is_intersecting.then_some(intersect.distance)
+    }
+
+    // Distancias de entrada y salida del rayo a través del volumen del objeto, por ejemplo para
+    // medir el grosor de un objeto transparente. Los objetos sin volumen devuelven None.
+    fn ray_span(&self, _ray_origin: &Vec3, _ray_direction: &Vec3) -> Option<(f32, f32)> {
+        None
+    }
+
+    // Los objetos que devuelven false se ven normalmente pero no proyectan sombras
+    fn casts_shadow(&self) -> bool {
+        true
+    }
+
+    fn bounding_box(&self) -> Aabb;
+    fn as_any(&self) -> &dyn Any;
     fn as_any_mut(&mut self) -> &mut dyn Any;
-}
\ No newline at end of file
+}
+
+// Geometría compartida: varias instancias (`transform::Instance`) pueden apuntar al mismo objeto sin
+// copiarlo; el Arc se intersecta igual que el objeto al que apunta
+impl<T: RayIntersect + ?Sized + 'static> RayIntersect for Arc<T> {
+    fn ray_intersect(&self, ray_origin: &Vec3, ray_direction: &Vec3, t_min: f32, t_max: f32) -> Intersect {
+        (**self).ray_intersect(ray_origin, ray_direction, t_min, t_max)
+    }
+
+    fn intersect_any(&self, ray_origin: &Vec3, ray_direction: &Vec3, t_min: f32, t_max: f32) -> Option<f32> {
+        (**self).intersect_any(ray_origin, ray_direction, t_min, t_max)
+    }
+
+    fn ray_span(&self, ray_origin: &Vec3, ray_direction: &Vec3) -> Option<(f32, f32)> {
+        (**self).ray_span(ray_origin, ray_direction)
+    }
+
+    fn casts_shadow(&self) -> bool {
+        (**self).casts_shadow()
+    }
+
+    fn bounding_box(&self) -> Aabb {
+        (**self).bounding_box()
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        (**self).as_any()
+    }
+
+    // La geometría compartida no se puede modificar a través de una instancia
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        self
+    }
+}
@@ -0,0 +1,31 @@
+pub mod color;
+pub mod material;
+pub mod intersect;
+pub mod camera;
+pub mod light;
+pub mod texture;
+pub mod noise;
+pub mod cube;
+pub mod sphere;
+pub mod plane;
+pub mod triangle;
+pub mod mesh;
+pub mod transform;
+pub mod group;
+pub mod bvh;
+pub mod exposure;
+pub mod aabb;
+pub mod wireframe;
+pub mod overlay;
+pub mod background;
+pub mod scene;
+pub mod scene_file;
+pub mod terrain;
+pub mod sampling;
+pub mod render;
+pub mod gbuffer;
+pub mod aov;
+pub mod denoise;
+pub mod accumulation;
+pub mod export;
+pub mod upscale;
@@ -1,10 +1,26 @@
 use nalgebra_glm::Vec3;
 use crate::color::Color;
 
+// Distancia a la que se coloca el sol: lo bastante lejos para que sus rayos sean casi paralelos
+// y la sombra no se atenúe con la distancia al objeto que la proyecta
+pub const SUN_DISTANCE: f32 = 1000.0;
+
+// Forma de una luz de área (radio mayor que 0), que define la forma de la penumbra
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum LightShape {
+    Sphere,               // Lámpara esférica: desde cualquier receptor se ve como un disco de frente
+    Disk { normal: Vec3 },  // Panel plano con orientación fija: visto de lado se achata y su sombra también
+}
+
 pub struct Light {
     pub position: Vec3,  // Posición de la luz en el espacio
     pub color: Color,    // Color de la luz (normalmente blanco)
     pub intensity: f32,  // Intensidad de la luz
+    pub subtractive: bool,  // Si es true, su contribución se resta en vez de sumarse (para oscurecer zonas a propósito)
+    pub radius: f32,     // Radio de la luz de área; 0 es una luz puntual con sombras duras
+    pub shape: LightShape,  // Forma de la luz de área; no importa si el radio es 0
+    pub diffuse: bool,   // Si aporta iluminación difusa; apagarlo deja, por ejemplo, una luz de contorno solo con brillos
+    pub specular: bool,  // Si aporta brillos especulares; apagarlo deja una luz de relleno que no genera reflejos
 }
 
 impl Light {
@@ -13,6 +29,74 @@ impl Light {
             position,
             color,
             intensity,
+            subtractive: false,
+            radius: 0.0,
+            shape: LightShape::Sphere,
+            diffuse: true,
+            specular: true,
+        }
+    }
+
+    // Luz de sol lejana en la dirección dada por azimut (desde -Z, girando hacia +X) y elevación,
+    // ambos en grados. Es blanca cálida, más anaranjada y tenue cuando el sol está bajo.
+    pub fn directional_sun(azimuth: f32, elevation: f32) -> Self {
+        let (azimuth, elevation) = (azimuth.to_radians(), elevation.to_radians());
+        let direction = Vec3::new(
+            elevation.cos() * azimuth.sin(),
+            elevation.sin(),
+            -elevation.cos() * azimuth.cos(),
+        );
+        let day = elevation.sin().clamp(0.0, 1.0);
+
+        Light {
+            position: direction * SUN_DISTANCE,
+            color: Color::new(255, 170, 100).lerp(&Color::new(255, 246, 228), day),
+            intensity: 0.4 + 0.9 * day,  // Siempre por encima del umbral de luz ambiental
+            subtractive: false,
+            radius: 0.0,
+            shape: LightShape::Sphere,
+            diffuse: true,
+            specular: true,
         }
     }
+
+    // Luz que oscurece en lugar de iluminar: no es física, pero sirve para dirigir la atención
+    pub fn subtractive(position: Vec3, color: Color, intensity: f32) -> Self {
+        Light {
+            subtractive: true,
+            ..Light::new(position, color, intensity)
+        }
+    }
+
+    // Las luces tenues se tratan como luz ambiental: no tienen dirección ni proyectan sombras
+    pub fn is_ambient(&self) -> bool {
+        self.intensity <= 0.3
+    }
+}
+
+
+// Color aproximado de un cuerpo negro a la temperatura dada, en Kelvin (aproximación de Tanner
+// Helland, válida entre 1000 K y 40000 K): ~1900 K es una vela, 6500 K es blanco de día.
+pub fn color_from_kelvin(kelvin: f32) -> Color {
+    let t = kelvin.clamp(1000.0, 40000.0) / 100.0;
+
+    let r = if t <= 66.0 {
+        255.0
+    } else {
+        329.699 * (t - 60.0).powf(-0.133_204_76)
+    };
+    let g = if t <= 66.0 {
+        99.470_8 * t.ln() - 161.119_57
+    } else {
+        288.122_16 * (t - 60.0).powf(-0.075_514_85)
+    };
+    let b = if t >= 66.0 {
+        255.0
+    } else if t <= 19.0 {
+        0.0
+    } else {
+        138.517_73 * (t - 10.0).ln() - 305.044_8
+    };
+
+    Color::new(r.clamp(0.0, 255.0) as u8, g.clamp(0.0, 255.0) as u8, b.clamp(0.0, 255.0) as u8)
 }
@@ -1,279 +1,30 @@
-mod color;
-mod material;
-mod intersect;
-mod camera;
-mod light;
-mod texture;
-mod cube;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
 
-use std::time::Instant;
-
-use material::Material;
 use nalgebra_glm::Vec3;
-use crate::intersect::{RayIntersect, Intersect};
-use camera::Camera;
-use rayon::prelude::*;
-use crate::light::Light;
-use crate::cube::Cube;
-use image::GenericImageView;
-use crate::texture::Texture;
-use crate::color::Color;
-
-
-
-
-fn load_texture(filename: &str) -> Texture {
-    let img = image::open(filename).expect("Failed to load texture");
-    let (width, height) = img.dimensions();
-    let mut data = Vec::new();
-
-    for y in (0..height).rev() {  // Flip vertically
-        for x in 0..width {
-            let pixel = img.get_pixel(x, y);
-            data.push(Color::new(pixel[0], pixel[1], pixel[2]));
-            }
-        }
-
-    Texture { width: width as usize, height: height as usize, data }
-}
-
-
-pub fn reflect(incident: &Vec3, normal: &Vec3) -> Vec3 {
-    incident - 2.0 * incident.dot(normal) * normal
-}
-
-pub fn refract(incident: &Vec3, normal: &Vec3, eta_t: f32) -> Vec3 {
-    let cosi = -incident.dot(normal).max(-1.0).min(1.0);
-
-    let (n_cosi, eta, n_normal);
-
-    if cosi < 0.0 {
-        // Ray is entering the object
-        n_cosi = -cosi;
-        eta = 1.0 / eta_t;
-        n_normal = -*normal;
-    } else {
-        // Ray is leaving the object
-        n_cosi = cosi;
-        eta = eta_t;
-        n_normal = *normal;
-    }
-
-    let k = 1.0 - eta * eta * (1.0 - n_cosi * n_cosi);
-
-    if k < 0.0 {
-        // Total internal reflection, return reflected ray
-        reflect(incident, &n_normal)
-    } else {
-        eta * incident + (eta * n_cosi - k.sqrt()) * n_normal
-    }
-}
-
-
-pub fn cast_shadow(
-    intersect: &Intersect,
-    light: &Light,
-    objects: &[Box<dyn RayIntersect>],
-) -> f32 {
-    // Dirección hacia la luz
-    let light_dir = (light.position - intersect.point).normalize();
-    // Desplazamos ligeramente el origen del rayo de sombra en la dirección de la normal para evitar el acné
-    let shadow_ray_origin = intersect.point + intersect.normal * 1e-3;
-
-    let mut shadow_intensity = 0.0;
-
-    // Lanzamos un rayo de sombra para cada objeto
-    for object in objects {
-        let shadow_intersect = object.ray_intersect(&shadow_ray_origin, &light_dir);
-        if shadow_intersect.is_intersecting {
-            // Ajustamos la intensidad de la sombra en función de la distancia
-            let distance_to_object = (shadow_intersect.point - intersect.point).magnitude();
-            let distance_to_light = (light.position - intersect.point).magnitude();
-            
-            // Si el objeto está entre el punto de intersección y la luz, ajustamos la sombra
-            if distance_to_object < distance_to_light {
-                shadow_intensity = 1.0 - (distance_to_object / distance_to_light).min(1.0);
-                break;
-            }
-        }
-    }
-
-    shadow_intensity
-}
-
-
-pub fn cast_ray(
-    ray_origin: &Vec3,
-    ray_direction: &Vec3,
-    objects: &[Box<dyn RayIntersect>],
-    lights: &[Light],
-    depth: u32,
-) -> color::Color {
-    if depth > 1 {
-        return color::Color::new(4, 12, 36);  // Color de fondo
-    }
-
-    let mut closest_intersection = Intersect::empty();
-    let mut closest_distance = f32::INFINITY;
-
-    // Buscar la intersección más cercana con cualquier objeto
-    for object in objects {
-        let intersection = object.ray_intersect(ray_origin, ray_direction);
-        if intersection.is_intersecting && intersection.distance < closest_distance {
-            closest_distance = intersection.distance;
-            closest_intersection = intersection;
-        }
-    }
-
-    if !closest_intersection.is_intersecting {
-        return color::Color::new(4, 12, 36);  // Color del cielo o fondo
-    }
-
-    // Obtener el color difuso del material
-    let diffuse_color = closest_intersection
-        .material
-        .get_diffuse_color(closest_intersection.u, closest_intersection.v);
-
-    // Inicializar el color final
-    let mut final_color = color::Color::new(0, 0, 0);
-
-    // Iterar sobre cada luz
-    for light in lights {
-        // Si la luz es ambiental, sumamos su contribución y continuamos
-        if light.intensity <= 0.3 {
-            final_color = color::Color {
-                r: (final_color.r as f32 + diffuse_color.r as f32 * light.intensity).min(255.0) as u8,
-                g: (final_color.g as f32 + diffuse_color.g as f32 * light.intensity).min(255.0) as u8,
-                b: (final_color.b as f32 + diffuse_color.b as f32 * light.intensity).min(255.0) as u8,
-            };
-            continue;
-        }
-
-        // Calcular la dirección de la luz y la intensidad difusa usando la ley de Lambert
-        let light_dir = (light.position - closest_intersection.point).normalize();
-        let diffuse_intensity = closest_intersection.normal.dot(&light_dir).max(0.0);
-
-        // Calcular la intensidad de la sombra
-        let shadow_intensity = cast_shadow(&closest_intersection, light, objects);
-        let light_intensity = light.intensity * (1.0 - shadow_intensity);
-
-        // Componente difusa
-        let diffuse = color::Color {
-            r: (diffuse_color.r as f32 * closest_intersection.material.albedo[0] * diffuse_intensity * light_intensity).min(255.0) as u8,
-            g: (diffuse_color.g as f32 * closest_intersection.material.albedo[0] * diffuse_intensity * light_intensity).min(255.0) as u8,
-            b: (diffuse_color.b as f32 * closest_intersection.material.albedo[0] * diffuse_intensity * light_intensity).min(255.0) as u8,
-        };
-
-        // Componente especular usando el modelo de Phong
-        let view_dir = (ray_origin - closest_intersection.point).normalize();
-        let reflect_dir = reflect(&-light_dir, &closest_intersection.normal).normalize();
-        let specular_intensity = view_dir
-            .dot(&reflect_dir)
-            .max(0.0)
-            .powf(closest_intersection.material.specular);
-        let specular = color::Color {
-            r: (light.color.r as f32 * closest_intersection.material.albedo[1] * specular_intensity * light_intensity).min(255.0) as u8,
-            g: (light.color.g as f32 * closest_intersection.material.albedo[1] * specular_intensity * light_intensity).min(255.0) as u8,
-            b: (light.color.b as f32 * closest_intersection.material.albedo[1] * specular_intensity * light_intensity).min(255.0) as u8,
-        };
-
-        // Sumar las contribuciones de esta luz al color final
-        final_color = color::Color {
-            r: (final_color.r as u32 + diffuse.r as u32 + specular.r as u32).min(255) as u8,
-            g: (final_color.g as u32 + diffuse.g as u32 + specular.g as u32).min(255) as u8,
-            b: (final_color.b as u32 + diffuse.b as u32 + specular.b as u32).min(255) as u8,
-        };
-    }
-
-    // Componente de reflexión
-    let reflectivity = closest_intersection.material.albedo[2];
-    let mut reflect_color = color::Color::new(0, 0, 0);
-    if reflectivity > 0.0 {
-        let reflect_origin = closest_intersection.point + closest_intersection.normal * 1e-3;
-        let reflect_dir = reflect(&-ray_direction, &closest_intersection.normal).normalize();
-        reflect_color = cast_ray(&reflect_origin, &reflect_dir, objects, lights, depth + 1);
-        reflect_color = color::Color {
-            r: (reflect_color.r as f32 * reflectivity).min(255.0) as u8,
-            g: (reflect_color.g as f32 * reflectivity).min(255.0) as u8,
-            b: (reflect_color.b as f32 * reflectivity).min(255.0) as u8,
-        };
-    }
-
-    // Componente de refracción
-    let transparency = closest_intersection.material.albedo[3];
-    let mut refract_color = color::Color::new(0, 0, 0);
-    if transparency > 0.0 {
-        let refract_dir = refract(&ray_direction, &closest_intersection.normal, closest_intersection.material.refractive_index).normalize();
-        let refract_origin = closest_intersection.point - closest_intersection.normal * 1e-3;  // Evitar acné de sombras
-        refract_color = cast_ray(&refract_origin, &refract_dir, objects, lights, depth + 1);
-        refract_color = color::Color {
-            r: (refract_color.r as f32 * transparency).min(255.0) as u8,
-            g: (refract_color.g as f32 * transparency).min(255.0) as u8,
-            b: (refract_color.b as f32 * transparency).min(255.0) as u8,
-        };
-    }
-
-    // Combinar difusa, especular, reflejada y refractada
-    color::Color {
-        r: ((final_color.r as f32 * (1.0 - reflectivity - transparency)) + (reflect_color.r as f32 * reflectivity) + (refract_color.r as f32 * transparency)).min(255.0) as u8,
-        g: ((final_color.g as f32 * (1.0 - reflectivity - transparency)) + (reflect_color.g as f32 * reflectivity) + (refract_color.g as f32 * transparency)).min(255.0) as u8,
-        b: ((final_color.b as f32 * (1.0 - reflectivity - transparency)) + (reflect_color.b as f32 * reflectivity) + (refract_color.b as f32 * transparency)).min(255.0) as u8,
-    }
-}
-
-
-
-
-
-pub fn render(
-    framebuffer: &mut [u32], 
-    width: usize, 
-    height: usize, 
-    objects: &[Box<dyn RayIntersect>], 
-    camera: &Camera, 
-    lights: &[Light]
-) {
-    let chunk_size = 8;  // Tamaño de bloque para procesar en paralelo
-    framebuffer.par_chunks_mut(width * chunk_size).enumerate().for_each(|(chunk_idx, chunk)| {
-        let base_y = chunk_idx * chunk_size;
-    
-        for (y, row) in chunk.chunks_mut(width).enumerate() {
-            let screen_y = -((2.0 * (base_y + y) as f32) / height as f32 - 1.0);
-    
-            row.iter_mut().enumerate().for_each(|(x, pixel)| {
-                let screen_x = (2.0 * x as f32) / width as f32 - 1.0;
-                let screen_x = screen_x * (width as f32 / height as f32);
-    
-                let ray_direction = nalgebra_glm::normalize(&Vec3::new(screen_x, screen_y, -1.0));
-                let transformed_direction = camera.basis_change(&ray_direction);
-    
-                // Llamar a cast_ray una sola vez por píxel
-                let pixel_color = cast_ray(&camera.eye, &transformed_direction, objects, lights, 0);
-    
-                *pixel = ((pixel_color.r as u32) << 16)
-                    | ((pixel_color.g as u32) << 8)
-                    | (pixel_color.b as u32);
-            });
-        }
-    });    
-}
-
-
-
-fn create_cube(
-    center: Vec3,
-    size: f32,
-    materials: [Material; 6],
-    is_water: bool,
-) -> Box<Cube> {
-    Box::new(Cube {
-        center,
-        original_center: center, // Inicializamos original_center con center
-        size,
-        materials,
-        is_water,
-    })
-}
+use rayon::ThreadPool;
+use ray_tracing::color;
+use ray_tracing::material::{self, DetailBlend, DetailLayer, Material, MaterialLibrary};
+use ray_tracing::intersect::RayIntersect;
+use ray_tracing::camera::{load_camera, Camera, DEFAULT_FOV};
+use ray_tracing::light::{color_from_kelvin, Light};
+use ray_tracing::cube::Cube;
+use ray_tracing::bvh::Bvh;
+use ray_tracing::sphere::Sphere;
+use ray_tracing::plane::Plane;
+use ray_tracing::texture::TextureCache;
+use ray_tracing::noise::ValueNoise;
+use ray_tracing::color::Color;
+use ray_tracing::accumulation::Accumulator;
+use ray_tracing::exposure::{histogram_text, luminance_histogram, AutoExposure};
+use ray_tracing::wireframe::{draw_bounding_boxes, draw_crosshair};
+use ray_tracing::render::{auto_chunk_size, pick, render, render_stereo, thread_pool, DebugView, Pick, RenderConfig, RenderStats, ToneMap};
+use ray_tracing::overlay::{draw_text_panel, draw_text_panel_bottom};
+use ray_tracing::export::{export_top_down_map, save_histogram_png, save_png, save_rgba_png};
+use ray_tracing::scene::{load_from_json_with, Scene};
+use ray_tracing::upscale::{upscale_edge_aware, upscale_framebuffer};
+use ray_tracing::gbuffer::GBuffer;
+use ray_tracing::aov::Aovs;
 
 
 fn main() {
@@ -281,21 +32,19 @@ fn main() {
     let height = 600;
 
 
-    // Cargar las texturas desde archivos PNG
-    let grama_texture = load_texture("textures/grama.png");
-    let tierra_texture = load_texture("textures/tierraG.jpeg");
-    let tierra4_texture = load_texture("textures/tierra.jpeg");
-    let arena_texture = load_texture("textures/arena.jpeg");
-    let agua_texture = load_texture("textures/agua.jpeg");
-    let madera_texture = load_texture("textures/madera.jpeg");
-    let hoja_texture = load_texture("textures/hoja2.jpeg");
-    let cactus_texture = load_texture("textures/cactus.jpeg");
+    // Las texturas se cargan la primera vez que un material las pide y se comparten entre materiales
+    let mut textures = TextureCache::new();
 
     // Inicializar la cámara
     let eye = Vec3::new(8.0, 12.0, -25.0);
     let center = Vec3::new(0.0, 0.0, -1.0);
     let up = Vec3::new(0.0, 1.0, 0.0);
-    let mut camera = Camera { eye, center, up };
+    let default_camera = Camera { eye, center, up, fov: DEFAULT_FOV, lens: None };
+    // `camera.toml` permite cambiar la toma inicial sin recompilar
+    let camera = load_camera(CAMERA_CONFIG, default_camera.clone()).unwrap_or_else(|e| {
+        eprintln!("No se pudo cargar la cámara: {}", e);
+        default_camera
+    });
 
     // Inicializar las luces
     let lights = vec![
@@ -320,8 +69,16 @@ fn main() {
         specular: 50.0,
         albedo: [0.6, 0.3, 0.1, 0.1],
         refractive_index: 1.5,
-        has_texture: true,
-        texture: Some(tierra_texture),
+        texture: Some(textures.get("textures/tierraG.jpeg")),
+        emission_texture: None,
+        blend: None,
+        reflection_color: Color::new(255, 255, 255),
+        noise: Some(ValueNoise::new(7, 0.8, 0.15)),
+        thin_film: None,
+        anisotropy: None,
+        detail: None,
+        refraction_roughness: 0.0,
+        name: Some("tierra".into()),  // El mismo con que se registra en la biblioteca
     };
 
     let tierra_material4 = material::Material {
@@ -329,8 +86,22 @@ fn main() {
         specular: 50.0,
         albedo: [0.6, 0.3, 0.1, 0.1],
         refractive_index: 1.5,
-        has_texture: true,
-        texture: Some(tierra4_texture),
+        texture: Some(textures.get("textures/tierra.jpeg")),
+        emission_texture: None,
+        blend: None,
+        reflection_color: Color::new(255, 255, 255),
+        noise: None,
+        thin_film: None,
+        anisotropy: None,
+        // Grano fino de la misma tierra a 8 veces la escala para que la repetición no se note de cerca
+        detail: Some(DetailLayer {
+            texture: textures.get("textures/tierra.jpeg"),
+            scale: 8.0,
+            strength: 0.35,
+            mode: DetailBlend::Multiply,
+        }),
+        refraction_roughness: 0.0,
+        name: Some("tierra_lados".into()),
     };
 
     let grama_material = material::Material {
@@ -338,8 +109,16 @@ fn main() {
         specular: 50.0,
         albedo: [0.6, 0.3, 0.1, 0.1],
         refractive_index: 1.5,
-        has_texture: true,
-        texture: Some(grama_texture),
+        texture: Some(textures.get("textures/grama.png")),
+        emission_texture: None,
+        blend: None,
+        reflection_color: Color::new(255, 255, 255),
+        noise: None,
+        thin_film: None,
+        anisotropy: None,
+        detail: None,
+        refraction_roughness: 0.0,
+        name: Some("grama".into()),
     };
 
     let arena = material::Material {
@@ -347,8 +126,16 @@ fn main() {
         specular: 5.0,
         albedo: [0.9, 0.1, 0.0, 0.0],
         refractive_index: 1.5,
-        has_texture: true,
-        texture: Some(arena_texture),
+        texture: Some(textures.get("textures/arena.jpeg")),
+        emission_texture: None,
+        blend: None,
+        reflection_color: Color::new(255, 255, 255),
+        noise: Some(ValueNoise::new(11, 0.8, 0.15)),
+        thin_film: None,
+        anisotropy: None,
+        detail: None,
+        refraction_roughness: 0.0,
+        name: Some("arena".into()),
     };
 
     let agua = material::Material {
@@ -356,8 +143,16 @@ fn main() {
         specular: 50.0,
         albedo: [0.6, 0.3, 0.1, 0.1],
         refractive_index: 1.5,
-        has_texture: true,
-        texture: Some(agua_texture),
+        texture: Some(textures.get("textures/agua.jpeg")),
+        emission_texture: None,
+        blend: None,
+        reflection_color: Color::new(255, 255, 255),
+        noise: None,
+        thin_film: None,
+        anisotropy: None,
+        detail: None,
+        refraction_roughness: 0.0,
+        name: Some("agua".into()),
     };
 
     let madera = material::Material {
@@ -365,8 +160,16 @@ fn main() {
         specular: 10.0,
         albedo: [0.6, 0.3, 0.0, 0.0],
         refractive_index: 1.5,
-        has_texture: true,
-        texture: Some(madera_texture),
+        texture: Some(textures.get("textures/madera.jpeg")),
+        emission_texture: None,
+        blend: None,
+        reflection_color: Color::new(255, 255, 255),
+        noise: None,
+        thin_film: None,
+        anisotropy: None,
+        detail: None,
+        refraction_roughness: 0.0,
+        name: Some("madera".into()),
     };
 
     let hoja = material::Material {
@@ -374,8 +177,16 @@ fn main() {
         specular: 20.0,
         albedo: [0.7, 0.2, 0.0, 0.1],
         refractive_index: 1.5,
-        has_texture: true,
-        texture: Some(hoja_texture),
+        texture: Some(textures.get("textures/hoja2.jpeg")),
+        emission_texture: None,
+        blend: None,
+        reflection_color: Color::new(255, 255, 255),
+        noise: None,
+        thin_film: None,
+        anisotropy: None,
+        detail: None,
+        refraction_roughness: 0.0,
+        name: Some("hoja".into()),
     };
 
     let cactus = material::Material {
@@ -383,8 +194,16 @@ fn main() {
         specular: 15.0,
         albedo: [0.6, 0.2, 0.0, 0.0],
         refractive_index: 1.5,
-        has_texture: true,
-        texture: Some(cactus_texture),
+        texture: Some(textures.get("textures/cactus.jpeg")),
+        emission_texture: None,
+        blend: None,
+        reflection_color: Color::new(255, 255, 255),
+        noise: None,
+        thin_film: None,
+        anisotropy: None,
+        detail: None,
+        refraction_roughness: 0.0,
+        name: Some("cactus".into()),
     };
 
     // Crear un cubo con materiales para cada cara
@@ -473,35 +292,22 @@ let floor_positions = vec![
 let mut floor_cubes: Vec<Box<dyn RayIntersect>> = Vec::new();
 
 for (x, y, z) in floor_positions {
-    let materials = if y == 4.0 || y == 2.0 && z == -8.0 || (x == 8.0 && y == 0.0) {
+    let cube = if y == 4.0 || y == 2.0 && z == -8.0 || (x == 8.0 && y == 0.0) {
         // Si está en la capa superior o en posiciones específicas, usa grama en la parte superior
-        [
+        let materials = [
             tierra_material.clone(),  // Derecha (X+)
             tierra_material.clone(),  // Izquierda (X-)
             grama_material.clone(),   // Arriba (Y+)
             tierra_material4.clone(), // Abajo (Y-)
             tierra_material.clone(),  // Frente (Z+)
             tierra_material.clone(),  // Atrás (Z-)
-        ]
+        ];
+        Cube::new(Vec3::new(x, y, z), 2.0, materials, false) // No es agua
     } else {
         // De lo contrario, usa tierra_material4 en todas las caras
-        [
-            tierra_material4.clone(),
-            tierra_material4.clone(),
-            tierra_material4.clone(),
-            tierra_material4.clone(),
-            tierra_material4.clone(),
-            tierra_material4.clone(),
-        ]
+        Cube::uniform(Vec3::new(x, y, z), 2.0, tierra_material4.clone())
     };
-
-    let cube = create_cube(
-        Vec3::new(x, y, z),
-        2.0,
-        materials,
-        false, // No es agua
-    );
-    floor_cubes.push(cube);
+    floor_cubes.push(Box::new(cube));
 }
 
 
@@ -579,21 +385,7 @@ let arena_positions = vec![
 let mut arena_cubes: Vec<Box<dyn RayIntersect>> = Vec::new();
 
 for (x, y, z) in arena_positions {
-    let materials = [
-        arena.clone(), // Derecha (X+)
-        arena.clone(), // Izquierda (X-)
-        arena.clone(), // Arriba (Y+)
-        arena.clone(), // Abajo (Y-)
-        arena.clone(), // Frente (Z+)
-        arena.clone(), // Atrás (Z-)
-    ];
-
-    let cube = create_cube(
-        Vec3::new(x, y, z),
-        2.0,
-        materials,
-        false, 
-    );
+    let cube = Box::new(Cube::uniform(Vec3::new(x, y, z), 2.0, arena.clone()));
 
     arena_cubes.push(cube);
 }
@@ -643,21 +435,8 @@ let agua_positions = vec![
 let mut agua_cubes: Vec<Box<dyn RayIntersect>> = Vec::new();
 
 for (x, y, z) in agua_positions {
-    let materials = [
-        agua.clone(), // Derecha (X+)
-        agua.clone(), // Izquierda (X-)
-        agua.clone(), // Arriba (Y+)
-        agua.clone(), // Abajo (Y-)
-        agua.clone(), // Frente (Z+)
-        agua.clone(), // Atrás (Z-)
-    ];
-
-    let cube = create_cube(
-        Vec3::new(x, y, z),
-        2.0,
-        materials,
-        true, // Es agua
-    );
+    let mut cube = Box::new(Cube::uniform(Vec3::new(x, y, z), 2.0, agua.clone()));
+    cube.is_water = true;
 
     agua_cubes.push(cube);
 }
@@ -674,21 +453,7 @@ let madera_positions = vec![
 let mut madera_cubes: Vec<Box<dyn RayIntersect>> = Vec::new();
 
 for (x, y, z) in madera_positions {
-    let materials = [
-        madera.clone(), // Derecha (X+)
-        madera.clone(), // Izquierda (X-)
-        madera.clone(), // Arriba (Y+)
-        madera.clone(), // Abajo (Y-)
-        madera.clone(), // Frente (Z+)
-        madera.clone(), // Atrás (Z-)
-    ];
-
-    let cube = create_cube(
-        Vec3::new(x, y, z),
-        2.0,
-        materials,
-        false, // No es agua
-    );
+    let cube = Box::new(Cube::uniform(Vec3::new(x, y, z), 2.0, madera.clone()));
 
     madera_cubes.push(cube);
 }
@@ -741,21 +506,7 @@ let hoja_positions = vec![
 let mut hoja_cubes: Vec<Box<dyn RayIntersect>> = Vec::new();
 
 for (x, y, z) in hoja_positions {
-    let materials = [
-        hoja.clone(), // Derecha (X+)
-        hoja.clone(), // Izquierda (X-)
-        hoja.clone(), // Arriba (Y+)
-        hoja.clone(), // Abajo (Y-)
-        hoja.clone(), // Frente (Z+)
-        hoja.clone(), // Atrás (Z-)
-    ];
-
-    let cube = create_cube(
-        Vec3::new(x, y, z),
-        2.0,
-        materials,
-        false, // No es agua
-    );
+    let cube = Box::new(Cube::uniform(Vec3::new(x, y, z), 2.0, hoja.clone()));
 
     hoja_cubes.push(cube);
 }
@@ -771,21 +522,7 @@ let cactus_positions = vec![
 let mut cactus_cubes: Vec<Box<dyn RayIntersect>> = Vec::new();
 
 for (x, y, z) in cactus_positions {
-    let materials = [
-        cactus.clone(), // Derecha (X+)
-        cactus.clone(), // Izquierda (X-)
-        cactus.clone(), // Arriba (Y+)
-        cactus.clone(), // Abajo (Y-)
-        cactus.clone(), // Frente (Z+)
-        cactus.clone(), // Atrás (Z-)
-    ];
-
-    let cube = create_cube(
-        Vec3::new(x, y, z),
-        2.0,
-        materials,
-        false, // No es agua
-    );
+    let cube = Box::new(Cube::uniform(Vec3::new(x, y, z), 2.0, cactus.clone()));
 
     cactus_cubes.push(cube);
 }
@@ -799,6 +536,45 @@ for (x, y, z) in cactus_positions {
     objects.extend(hoja_cubes);
     objects.extend(cactus_cubes);
 
+    // Esfera de vidrio sobre la pradera, para ejercitar la reflexión, la refracción y las UV esféricas,
+    // y una de cromo a su lado; ambas usan materiales de la biblioteca
+    let mut library = MaterialLibrary::with_presets();
+    let vidrio = library.instance("glass").expect("preset glass");
+    objects.push(Box::new(Sphere::new(Vec3::new(10.0, 2.2, -10.0), 1.2, vidrio)));
+    let cromo = library.instance("chrome").expect("preset chrome");
+    objects.push(Box::new(Sphere::new(Vec3::new(6.0, 1.8, -12.0), 0.8, cromo)));
+
+    // Mar alrededor de la isla: un solo plano de agua a media altura de la capa inferior de cubos
+    objects.push(Box::new(Plane::new(Vec3::new(0.0, SEA_LEVEL, 0.0), Vec3::new(0.0, 1.0, 0.0), Material::water())));
+
+    // Con `--scene mapa.json` la escena sale del archivo en vez de la isla de ejemplo. Sus objetos
+    // pueden nombrar los materiales de la isla (que tienen texturas) además de los de la biblioteca; por
+    // eso `F3` guarda los de la isla solo por su nombre.
+    library.insert("tierra", tierra_material.clone());
+    library.insert("tierra_lados", tierra_material4.clone());
+    library.insert("grama", grama_material.clone());
+    library.insert("arena", arena.clone());
+    library.insert("agua", agua.clone());
+    library.insert("madera", madera.clone());
+    library.insert("hoja", hoja.clone());
+    library.insert("cactus", cactus.clone());
+    let mut scene = match scene_path_from_args() {
+        Some(path) => match load_from_json_with(&path, &library) {
+            Ok(scene) => scene,
+            Err(e) => {
+                eprintln!("No se pudo cargar la escena: {}", e);
+                Scene::new(objects, lights, camera)
+            }
+        },
+        None => Scene::new(objects, lights, camera),
+    };
+
+    // Reportar errores comunes de la escena antes de empezar a renderizar
+    for warning in scene.validate() {
+        eprintln!("Advertencia de escena: {}", warning);
+    }
+    scene.objects = accelerate(std::mem::take(&mut scene.objects));
+
 
 // Ciclo principal del renderizado
 let mut framebuffer_high = vec![0; width * height];
@@ -822,25 +598,80 @@ let mut scene_changed = false;
 let mut time = 0.0f32;
 let amplitude = 0.5f32;
 let frequency = 1.0f32;
+// Exposición automática basada en la luminancia del cuadro anterior; empieza desactivada (X)
+let mut auto_exposure = AutoExposure::new(0.35, 0.1);
+// El fondo lo define la escena (por ejemplo, el cielo de `Scene::with_daylight`)
+let mut render_config = RenderConfig {
+    background: scene.background.clone(),
+    tone_map: tone_map_from_args(),
+    thread_pool: thread_pool_from_args(),
+    max_depth: max_depth_from_args(),
+    ..RenderConfig::default()
+};
+// Temperatura de color de la luz principal, en Kelvin; empieza en blanco de día
+let mut light_temperature = 6500.0f32;
+// Vista estereoscópica lado a lado para visores VR
+let mut stereo = false;
+// Vista previa en movimiento escalada con la guía del G-buffer en vez de vecino más cercano
+let mut edge_aware_preview = false;
+// Iteraciones del filtro à-trous sobre el cuadro final (F6); 0 lo desactiva
+let mut denoise_iterations = 0u32;
+// Superposición de depuración con las cajas delimitadoras
+let mut show_bounding_boxes = false;
+// Superposición con FPS, resolución y conteo de rayos
+let mut show_stats = false;
+let mut fps = 0.0f32;
+// Sombras en el cuadro interactivo; el cuadro final en alta resolución siempre las calcula
+let mut interactive_shadows = true;
+// Modo de colocación: mira en el cursor y coordenadas del punto del mundo que hay debajo
+let mut placement_mode = false;
+let mut last_cursor: Option<(f32, f32)> = None;
+// Posición del cursor en el cuadro anterior mientras se arrastra con el botón central
+let mut last_pan_cursor: Option<(f32, f32)> = None;
+// Muestras desplazadas que se acumulan mientras la cámara está quieta, para suavizar la imagen
+let mut accumulator = Accumulator::new(width, height);
+// Último cuadro mostrado, sin la mira, para redibujarla cuando solo se mueve el cursor
+let mut presented = vec![0u32; width * height];
+// Multiplicador de la velocidad del cuadro anterior, para avisar cuando Shift o Ctrl lo cambian
+let mut speed_scale = 1.0f32;
+// Exposición manual (+ / -), que se multiplica con la automática
+let mut manual_exposure = 1.0f32;
+window.set_title(&window_title(manual_exposure));
 
 while window.is_open() && !window.is_key_down(minifb::Key::Escape) {
     camera_moved = false;
 
+    // Mientras se mantiene Shift la cámara va más rápido y mientras se mantiene Ctrl, más lento; al
+    // soltarlos vuelve a la velocidad base
+    let mut new_speed_scale = 1.0;
+    if window.is_key_down(minifb::Key::LeftShift) || window.is_key_down(minifb::Key::RightShift) {
+        new_speed_scale *= FAST_SPEED_SCALE;
+    }
+    if window.is_key_down(minifb::Key::LeftCtrl) || window.is_key_down(minifb::Key::RightCtrl) {
+        new_speed_scale *= SLOW_SPEED_SCALE;
+    }
+    if new_speed_scale != speed_scale {
+        speed_scale = new_speed_scale;
+        println!("Velocidad de la cámara: x{}", speed_scale);
+    }
+    let move_speed = BASE_MOVE_SPEED * speed_scale;
+    let orbit_speed = BASE_ORBIT_SPEED * speed_scale;
+
     // Manejo de teclas de flecha para la órbita
     if window.is_key_down(minifb::Key::Left) {
-        camera.orbit(0.05, 0.0);
+        scene.camera.orbit(orbit_speed, 0.0);
         camera_moved = true;
     }
     if window.is_key_down(minifb::Key::Right) {
-        camera.orbit(-0.05, 0.0);
+        scene.camera.orbit(-orbit_speed, 0.0);
         camera_moved = true;
     }
     if window.is_key_down(minifb::Key::Up) {
-        camera.orbit(0.0, 0.05);
+        scene.camera.orbit(0.0, orbit_speed);
         camera_moved = true;
     }
     if window.is_key_down(minifb::Key::Down) {
-        camera.orbit(0.0, -0.05);
+        scene.camera.orbit(0.0, -orbit_speed);
         camera_moved = true;
     }
 
@@ -851,103 +682,585 @@ while window.is_open() && !window.is_key_down(minifb::Key::Escape) {
 
     // Movimiento hacia adelante y hacia atrás (W/S)
     if window.is_key_down(minifb::Key::W) {
-        forward += 0.1;
+        forward += move_speed;
     }
     if window.is_key_down(minifb::Key::S) {
-        forward -= 0.1;
+        forward -= move_speed;
     }
 
     // Movimiento lateral (A/D)
     if window.is_key_down(minifb::Key::A) {
-        rightward -= 0.1;
+        rightward -= move_speed;
     }
     if window.is_key_down(minifb::Key::D) {
-        rightward += 0.1;
+        rightward += move_speed;
     }
 
     // Movimiento vertical (Q/E o puedes usar otras teclas)
     if window.is_key_down(minifb::Key::Q) {
-        vertical += 0.1;
+        vertical += move_speed;
     }
     if window.is_key_down(minifb::Key::E) {
-        vertical -= 0.1;
+        vertical -= move_speed;
     }
 
     // Aplicar movimiento de la cámara
     if forward != 0.0 || rightward != 0.0 {
-        camera.move_camera(forward, rightward);
+        scene.camera.move_camera(forward, rightward);
         camera_moved = true;
     }
 
     if vertical != 0.0 {
-        camera.move_vertical(vertical);
+        scene.camera.move_vertical(vertical);
         camera_moved = true;
     }
 
+    // Arrastrar con el botón central desplaza la vista en el plano de la pantalla; el punto al que mira
+    // la cámara sigue al cursor
+    let pan_cursor = if window.get_mouse_down(minifb::MouseButton::Middle) {
+        window.get_mouse_pos(minifb::MouseMode::Pass)
+    } else {
+        None
+    };
+    if let (Some((x, y)), Some((last_x, last_y))) = (pan_cursor, last_pan_cursor) {
+        if (x, y) != (last_x, last_y) {
+            let distance = (scene.camera.center - scene.camera.eye).norm();
+            let world_per_pixel = 2.0 * scene.camera.half_height() * distance / height as f32;
+            scene.camera.pan(-(x - last_x) * world_per_pixel, (y - last_y) * world_per_pixel);
+            camera_moved = true;
+        }
+    }
+    last_pan_cursor = pan_cursor;
+
+    // Activar o desactivar la exposición automática (X)
+    if window.is_key_pressed(minifb::Key::X, minifb::KeyRepeat::No) {
+        auto_exposure.enabled = !auto_exposure.enabled;
+        if !auto_exposure.enabled {
+            auto_exposure.exposure = 1.0;
+        }
+        println!("Exposición automática: {}", if auto_exposure.enabled { "activada" } else { "desactivada" });
+        scene_changed = true;
+    }
+
+    // Mover la primera luz no ambiental (J/L en X, U/O en Y, I/K en Z)
+    let mut light_offset = Vec3::new(0.0, 0.0, 0.0);
+    if window.is_key_down(minifb::Key::J) {
+        light_offset.x -= 0.2;
+    }
+    if window.is_key_down(minifb::Key::L) {
+        light_offset.x += 0.2;
+    }
+    if window.is_key_down(minifb::Key::U) {
+        light_offset.y += 0.2;
+    }
+    if window.is_key_down(minifb::Key::O) {
+        light_offset.y -= 0.2;
+    }
+    if window.is_key_down(minifb::Key::I) {
+        light_offset.z += 0.2;
+    }
+    if window.is_key_down(minifb::Key::K) {
+        light_offset.z -= 0.2;
+    }
+
+    if light_offset != Vec3::zeros() {
+        if let Some(light) = scene.lights.iter_mut().find(|light| !light.is_ambient()) {
+            light.position += light_offset;
+            println!(
+                "Posición de la luz: ({:.2}, {:.2}, {:.2})",
+                light.position.x, light.position.y, light.position.z
+            );
+            scene_changed = true;
+        }
+    }
+
+    // Cambiar la temperatura de color de la luz principal: más cálida ([) o más fría (])
+    let mut temperature_step = 0.0;
+    if window.is_key_pressed(minifb::Key::LeftBracket, minifb::KeyRepeat::Yes) {
+        temperature_step -= 500.0;
+    }
+    if window.is_key_pressed(minifb::Key::RightBracket, minifb::KeyRepeat::Yes) {
+        temperature_step += 500.0;
+    }
+
+    if temperature_step != 0.0 {
+        if let Some(light) = scene.lights.iter_mut().find(|light| !light.is_ambient()) {
+            light_temperature = (light_temperature + temperature_step).clamp(1000.0, 40000.0);
+            light.color = color_from_kelvin(light_temperature);
+            println!("Temperatura de la luz: {:.0} K", light_temperature);
+            scene_changed = true;
+        }
+    }
+
+    // Subir (+) o bajar (-) la exposición un cuarto de paso por pulsación
+    let mut exposure_steps = 0.0f32;
+    if window.is_key_pressed(minifb::Key::Equal, minifb::KeyRepeat::Yes)
+        || window.is_key_pressed(minifb::Key::NumPadPlus, minifb::KeyRepeat::Yes)
+    {
+        exposure_steps += 1.0;
+    }
+    if window.is_key_pressed(minifb::Key::Minus, minifb::KeyRepeat::Yes)
+        || window.is_key_pressed(minifb::Key::NumPadMinus, minifb::KeyRepeat::Yes)
+    {
+        exposure_steps -= 1.0;
+    }
+
+    if exposure_steps != 0.0 {
+        manual_exposure = (manual_exposure * EXPOSURE_STEP.powf(exposure_steps)).clamp(MIN_EXPOSURE, MAX_EXPOSURE);
+        window.set_title(&window_title(manual_exposure));
+        scene_changed = true;
+    }
+
+    // Activar o desactivar las sombras mientras se navega (T)
+    if window.is_key_pressed(minifb::Key::T, minifb::KeyRepeat::No) {
+        interactive_shadows = !interactive_shadows;
+        println!("Sombras al navegar: {}", if interactive_shadows { "activadas" } else { "desactivadas" });
+        scene_changed = true;
+    }
+
+    // Activar o desactivar el modo de colocación (C)
+    if window.is_key_pressed(minifb::Key::C, minifb::KeyRepeat::No) {
+        placement_mode = !placement_mode;
+        scene_changed = true;
+    }
+
+    // Imprimir el punto bajo la mira para copiarlo a la escena (Enter)
+    if placement_mode && window.is_key_pressed(minifb::Key::Enter, minifb::KeyRepeat::No) {
+        match last_cursor.and_then(|(x, y)| pick(&scene.objects, &scene.camera, &render_config, x, y, width, height)) {
+            Some(hit) => println!(
+                "Vec3::new({:.2}, {:.2}, {:.2})  // objeto {}, normal ({:.2}, {:.2}, {:.2})",
+                hit.point.x, hit.point.y, hit.point.z, hit.object, hit.normal.x, hit.normal.y, hit.normal.z
+            ),
+            None => println!("No hay ningún objeto bajo la mira"),
+        }
+    }
+
+    // Mostrar u ocultar las cajas delimitadoras (B)
+    if window.is_key_pressed(minifb::Key::B, minifb::KeyRepeat::No) {
+        show_bounding_boxes = !show_bounding_boxes;
+        scene_changed = true;
+    }
+
+    // Alternar la vista en escala de grises para revisar la luminancia (G)
+    if window.is_key_pressed(minifb::Key::G, minifb::KeyRepeat::No) {
+        render_config.saturation = if render_config.saturation == 0.0 { 1.0 } else { 0.0 };
+        scene_changed = true;
+    }
+
+    // Mostrar las normales como color para revisar la geometría (N)
+    if window.is_key_pressed(minifb::Key::N, minifb::KeyRepeat::No) {
+        render_config.debug_view = match render_config.debug_view {
+            DebugView::Normals => DebugView::Off,
+            _ => DebugView::Normals,
+        };
+        scene_changed = true;
+    }
+
+    // Mostrar las coordenadas UV como color para revisar el mapeo de texturas (Y)
+    if window.is_key_pressed(minifb::Key::Y, minifb::KeyRepeat::No) {
+        render_config.debug_view = match render_config.debug_view {
+            DebugView::Uv => DebugView::Off,
+            _ => DebugView::Uv,
+        };
+        scene_changed = true;
+    }
+
+    // Mostrar solo la luz directa para revisar el sombreado y las sombras (H)
+    if window.is_key_pressed(minifb::Key::H, minifb::KeyRepeat::No) {
+        render_config.debug_view = match render_config.debug_view {
+            DebugView::DirectLighting => DebugView::Off,
+            _ => DebugView::DirectLighting,
+        };
+        scene_changed = true;
+    }
+
+    // Guardar el cuadro actual en alta resolución como PNG, con el operador de tono elegido (R)
+    if window.is_key_pressed(minifb::Key::R, minifb::KeyRepeat::No) {
+        let export_config = RenderConfig {
+            time_budget: None,
+            shadows: true,
+            chunk_size: auto_chunk_size(height, &render_config),
+            ..render_config.clone()
+        };
+        let mut image = vec![0; width * height];
+        render(&mut image, width, height, &scene.objects, &scene.camera, &scene.lights, &export_config);
+        match save_png("render.png", &image, width, height) {
+            Ok(()) => println!("Cuadro guardado en render.png ({:?})", export_config.tone_map),
+            Err(e) => eprintln!("No se pudo guardar render.png: {}", e),
+        }
+    }
+
+    // Imprimir el histograma de luminancia del cuadro y guardarlo en histogram.png, para ajustar la
+    // exposición y el operador de tono viendo qué se recorta (F2)
+    if window.is_key_pressed(minifb::Key::F2, minifb::KeyRepeat::No) {
+        let export_config = RenderConfig {
+            time_budget: None,
+            shadows: true,
+            chunk_size: auto_chunk_size(height, &render_config),
+            ..render_config.clone()
+        };
+        let mut image = vec![0; width * height];
+        render(&mut image, width, height, &scene.objects, &scene.camera, &scene.lights, &export_config);
+        let histogram = luminance_histogram(&image, HISTOGRAM_BINS);
+        print!("{}", histogram_text(&histogram));
+        if let Err(e) = save_histogram_png("histogram.png", &histogram, 256, 128) {
+            eprintln!("No se pudo guardar histogram.png: {}", e);
+        }
+    }
+
+    // Guardar cada parte del sombreado del cuadro (difusa, especular, sombra, reflejo...) en su
+    // propio aov_<parte>.png, para componerlas aparte o ver de dónde sale el color de un píxel (F5)
+    if window.is_key_pressed(minifb::Key::F5, minifb::KeyRepeat::No) {
+        let aov_config = RenderConfig { time_budget: None, shadows: true, ..render_config.clone() };
+        let aovs = Aovs::render(width, height, &scene.objects, &scene.camera, &scene.lights, &aov_config);
+        for (name, layer) in aovs.layers() {
+            let path = format!("aov_{}.png", name);
+            if let Err(e) = save_png(&path, &layer, width, height) {
+                eprintln!("No se pudo guardar {}: {}", path, e);
+            }
+        }
+        println!("Capas guardadas en aov_*.png");
+    }
+
+    // Guardar el cuadro actual como PNG con canal alfa, transparente donde solo se ve el fondo (M)
+    if window.is_key_pressed(minifb::Key::M, minifb::KeyRepeat::No) {
+        let matte_config = RenderConfig {
+            alpha_matte: true,
+            time_budget: None,
+            shadows: true,
+            chunk_size: auto_chunk_size(height, &render_config),
+            ..render_config.clone()
+        };
+        let mut matte = vec![0; width * height];
+        render(&mut matte, width, height, &scene.objects, &scene.camera, &scene.lights, &matte_config);
+        match save_rgba_png("matte.png", &matte, width, height) {
+            Ok(()) => println!("Matte guardado en matte.png"),
+            Err(e) => eprintln!("No se pudo guardar matte.png: {}", e),
+        }
+    }
+
+    // Exportar un mapa de toda la escena vista desde arriba (P)
+    if window.is_key_pressed(minifb::Key::P, minifb::KeyRepeat::No) {
+        match export_top_down_map("map.png", &scene, &RenderConfig { shadows: true, ..render_config.clone() }, (width, height)) {
+            Ok(()) => println!("Mapa guardado en map.png"),
+            Err(e) => eprintln!("No se pudo guardar map.png: {}", e),
+        }
+    }
+
+    // Alternar la vista estereoscópica lado a lado (V)
+    if window.is_key_pressed(minifb::Key::V, minifb::KeyRepeat::No) {
+        stereo = !stereo;
+        scene_changed = true;
+    }
+
+    // Guardar la escena actual, con la cámara y las luces tal como quedaron, en scene.json (F3)
+    if window.is_key_pressed(minifb::Key::F3, minifb::KeyRepeat::No) {
+        match scene.save_json_with(SCENE_FILE, &library) {
+            Ok(warnings) => {
+                for warning in warnings {
+                    eprintln!("Advertencia al guardar: {}", warning);
+                }
+                println!("Escena guardada en {}", SCENE_FILE);
+            }
+            Err(e) => eprintln!("No se pudo guardar la escena: {}", e),
+        }
+    }
+
+    // Alternar el escalado de la vista previa entre vecino más cercano y guiado por bordes (F4)
+    if window.is_key_pressed(minifb::Key::F4, minifb::KeyRepeat::No) {
+        edge_aware_preview = !edge_aware_preview;
+        scene_changed = true;
+    }
+
+    // Cambiar la intensidad del filtro de ruido del cuadro final: 0, 1, ..., MAX_DENOISE_ITERATIONS y de nuevo 0 (F6)
+    if window.is_key_pressed(minifb::Key::F6, minifb::KeyRepeat::No) {
+        denoise_iterations = (denoise_iterations + 1) % (MAX_DENOISE_ITERATIONS + 1);
+        println!("Filtro de ruido: {} iteraciones", denoise_iterations);
+        scene_changed = true;
+    }
+
+    // Mostrar u ocultar las estadísticas (F1)
+    if window.is_key_pressed(minifb::Key::F1, minifb::KeyRepeat::No) {
+        show_stats = !show_stats;
+        scene_changed = true;
+    }
+
     // Calcular delta_time
     let now = Instant::now();
     let delta_time = now.duration_since(last_frame_time).as_secs_f32();
     last_frame_time = now;
 
+    // FPS suavizado para que el número no salte en cada cuadro
+    if delta_time > 0.0 {
+        fps = 0.9 * fps + 0.1 / delta_time;
+    }
+
     // Incrementar el tiempo total de animación
     time += delta_time;
 
-    // Animar los cubos de agua
-    for object in objects.iter_mut() {
+    // Animar los cubos de agua. No cuenta como un cambio de la escena: si reiniciara la acumulación en
+    // cada cuadro, la imagen quieta nunca se refinaría
+    let mut water_moved = false;
+    for object in scene.objects.iter_mut() {
         if let Some(cube) = object.as_any_mut().downcast_mut::<Cube>() {
             // Verificar si el cubo es de agua
             if cube.is_water {
                 // Animar el cubo de agua, por ejemplo, moverlo en el eje Y
-                cube.center.y = cube.original_center.y + amplitude * (frequency * time).sin();
-                scene_changed = true;  // La escena ha cambiado
+                let y = cube.original_center.y + amplitude * (frequency * time).sin();
+                let center = cube.center();
+                cube.set_center(Vec3::new(center.x, y, center.z));
+                water_moved = true;
             }
         }
     }
+    // En estéreo no hay acumulación: el agua se sigue mostrando con la vista previa
+    if water_moved && stereo {
+        scene_changed = true;
+    }
 
     if camera_moved || scene_changed {
         // Renderizar en baja resolución para una actualización rápida
-        render(&mut framebuffer_low, width / 2, height / 2, &objects, &camera, &lights[..]);
-        let scaled_framebuffer = upscale_framebuffer(
-            &framebuffer_low,
-            width / 2,
-            height / 2,
-            width,
-            height,
-        );
-        window.update_with_buffer(&scaled_framebuffer, width, height).unwrap();
+        render_config.exposure = auto_exposure.exposure * manual_exposure;
+        render_config.chunk_size = auto_chunk_size(height / 2, &render_config);
+        // El cuadro interactivo no debe trabar la ventana: lo que no alcance a calcularse se queda como estaba
+        render_config.time_budget = Some(INTERACTIVE_TIME_BUDGET);
+        render_config.edge_samples = 0;
+        render_config.denoise_iterations = 0;
+        render_config.shadows = interactive_shadows;
+        let stats = if stereo {
+            render_stereo(&mut framebuffer_low, width / 2, height / 2, &scene, &render_config, INTEROCULAR_DISTANCE)
+        } else {
+            render(&mut framebuffer_low, width / 2, height / 2, &scene.objects, &scene.camera, &scene.lights, &render_config)
+        };
+        auto_exposure.update(&framebuffer_low);
+        // El escalado guiado necesita el G-buffer en ambas resoluciones; el de alta solo lanza rayos cerca
+        // de los bordes. En estéreo cada mitad tiene su propia cámara, así que ahí se usa siempre el
+        // vecino más cercano.
+        let mut scaled_framebuffer = if edge_aware_preview && !stereo {
+            let low_gbuffer = GBuffer::render(width / 2, height / 2, &scene.objects, &scene.camera, &render_config);
+            let high_gbuffer = GBuffer::render_guided(&low_gbuffer, width, height, &scene.objects, &scene.camera, &render_config);
+            upscale_edge_aware(&framebuffer_low, &low_gbuffer, &high_gbuffer)
+        } else {
+            upscale_framebuffer(
+                &framebuffer_low,
+                width / 2,
+                height / 2,
+                width,
+                height,
+            )
+        };
+        if show_bounding_boxes {
+            draw_bounding_boxes(&mut scaled_framebuffer, width, height, &scene.objects, &scene.camera, 0x00FF00);
+        }
+        if show_stats {
+            draw_text_panel(&mut scaled_framebuffer, width, height, &stats_lines(fps, &stats, (width / 2, height / 2)));
+        }
+        presented = scaled_framebuffer;
+        accumulator.reset();
         should_render = true;  // Marcar para renderizar en alta resolución en el próximo ciclo
         scene_changed = false; // Restablecer la bandera
     } else if should_render {
         // Renderizar en alta resolución
-        render(&mut framebuffer_high, width, height, &objects, &camera, &lights);
-        window.update_with_buffer(&framebuffer_high, width, height).unwrap();
+        render_config.exposure = auto_exposure.exposure * manual_exposure;
+        render_config.chunk_size = auto_chunk_size(height, &render_config);
+        render_config.time_budget = None;
+        // En el cuadro final suavizamos las siluetas supermuestreando solo los bordes entre objetos
+        render_config.edge_samples = EDGE_AA_SAMPLES;
+        render_config.denoise_iterations = denoise_iterations;
+        render_config.shadows = true;
+        let stats = if stereo {
+            render_stereo(&mut framebuffer_high, width, height, &scene, &render_config, INTEROCULAR_DISTANCE)
+        } else {
+            render(&mut framebuffer_high, width, height, &scene.objects, &scene.camera, &scene.lights, &render_config)
+        };
+        auto_exposure.update(&framebuffer_high);
+        if show_bounding_boxes {
+            draw_bounding_boxes(&mut framebuffer_high, width, height, &scene.objects, &scene.camera, 0x00FF00);
+        }
+        if show_stats {
+            draw_text_panel(&mut framebuffer_high, width, height, &stats_lines(fps, &stats, (width, height)));
+        }
+        presented.copy_from_slice(&framebuffer_high);
         should_render = false;  // Establecer a false después de renderizar
-    } else {
+    } else if !stereo && (accumulator.samples() < MAX_ACCUMULATED_SAMPLES || water_moved) {
+        // Cámara quieta: cada cuadro suma una muestra desplazada por píxel y la imagen se refina. Cada
+        // muestra ve el agua donde está en ese cuadro; al llenarse la ventana se empieza otra para que
+        // el agua siga moviéndose en pantalla
+        if accumulator.samples() >= MAX_ACCUMULATED_SAMPLES {
+            accumulator.reset();
+        }
+        accumulator.add_sample(&scene.objects, &scene.camera, &scene.lights, &render_config);
+        accumulator.resolve(&mut framebuffer_high, &render_config);
+        if show_bounding_boxes {
+            draw_bounding_boxes(&mut framebuffer_high, width, height, &scene.objects, &scene.camera, 0x00FF00);
+        }
+        if show_stats {
+            draw_text_panel(&mut framebuffer_high, width, height, &[format!("SAMPLES: {}", accumulator.samples())]);
+        }
+        presented.copy_from_slice(&framebuffer_high);
+    } else if !placement_mode || window.get_mouse_pos(minifb::MouseMode::Discard) == last_cursor {
+        // Nada nuevo que mostrar
         window.update();
+        continue;
+    }
+
+    // Mostrar el cuadro; en modo de colocación, con la mira y la lectura del punto bajo el cursor
+    if placement_mode {
+        last_cursor = window.get_mouse_pos(minifb::MouseMode::Discard);
+        let mut shown = presented.clone();
+        if let Some((x, y)) = last_cursor {
+            let hit = pick(&scene.objects, &scene.camera, &render_config, x, y, width, height);
+            draw_crosshair(&mut shown, width, height, (x, y), CROSSHAIR_SIZE, 0xFFFF00);
+            draw_text_panel_bottom(&mut shown, width, height, &placement_lines(hit));
+        }
+        window.update_with_buffer(&shown, width, height).unwrap();
+    } else {
+        window.update_with_buffer(&presented, width, height).unwrap();
     }
 }
 
-// Función para escalar el framebuffer de baja resolución al tamaño completo
-fn upscale_framebuffer(
-    low_res_buffer: &[u32],
-    low_width: usize,
-    low_height: usize,
-    high_width: usize,
-    high_height: usize,
-) -> Vec<u32> {
-    let mut high_res_buffer = vec![0; high_width * high_height];
-
-    for y in 0..high_height {
-        let src_y = y * low_height / high_height;
-        for x in 0..high_width {
-            let src_x = x * low_width / high_width;
-            let src_index = src_y * low_width + src_x;
-            let dst_index = y * high_width + x;
-            high_res_buffer[dst_index] = low_res_buffer[src_index];
+// Altura del plano de agua que rodea la isla
+const SEA_LEVEL: f32 = -0.4;
+
+// Archivo opcional con la posición inicial de la cámara
+const CAMERA_CONFIG: &str = "camera.toml";
+
+// Tiempo máximo para el cuadro de baja resolución que se muestra mientras la cámara se mueve
+const INTERACTIVE_TIME_BUDGET: Duration = Duration::from_millis(100);
+
+// Velocidad base de la cámara por cuadro: desplazamiento (WASD/QE) en unidades y órbita en radianes
+const BASE_MOVE_SPEED: f32 = 0.1;
+const BASE_ORBIT_SPEED: f32 = 0.05;
+// Multiplicador de la velocidad mientras se mantiene Shift (rápido) o Ctrl (lento)
+const FAST_SPEED_SCALE: f32 = 4.0;
+const SLOW_SPEED_SCALE: f32 = 0.25;
+
+// Factor de cada paso de exposición manual (un cuarto de paso fotográfico) y sus límites
+const EXPOSURE_STEP: f32 = 1.189_207_1;
+const MIN_EXPOSURE: f32 = 1.0 / 16.0;
+const MAX_EXPOSURE: f32 = 16.0;
+
+// Separación entre los ojos de la vista estereoscópica, en unidades del mundo
+const INTEROCULAR_DISTANCE: f32 = 0.3;
+
+// Muestras por píxel que se acumulan con la cámara quieta antes de dejar de refinar
+const MAX_ACCUMULATED_SAMPLES: u32 = 64;
+
+// Iteraciones máximas del filtro de ruido; cada una duplica el alcance, 4 ya promedia 31 píxeles a cada lado
+const MAX_DENOISE_ITERATIONS: u32 = 4;
+
+// Largo en píxeles de cada brazo de la mira del modo de colocación
+const CROSSHAIR_SIZE: f32 = 8.0;
+
+// Archivo donde F3 guarda la escena actual
+const SCENE_FILE: &str = "scene.json";
+
+// Intervalos de luminancia del histograma que se imprime con F2
+const HISTOGRAM_BINS: usize = 16;
+
+// Muestras por píxel en los bordes entre objetos del cuadro de alta resolución
+const EDGE_AA_SAMPLES: u32 = 4;
+
+// Operador de tono elegido con `--tonemap clamp|reinhard|aces`; sin la opción se usa Clamp
+fn tone_map_from_args() -> ToneMap {
+    let args: Vec<String> = std::env::args().collect();
+    match args.iter().position(|arg| arg == "--tonemap") {
+        Some(i) => match args.get(i + 1).and_then(|name| ToneMap::from_name(name)) {
+            Some(tone_map) => tone_map,
+            None => {
+                eprintln!("--tonemap espera clamp, reinhard o aces; se usa clamp");
+                ToneMap::Clamp
+            }
+        },
+        None => ToneMap::Clamp,
+    }
+}
+
+// Archivo de escena elegido con `--scene mapa.json`; None usa la isla de ejemplo
+fn scene_path_from_args() -> Option<String> {
+    let args: Vec<String> = std::env::args().collect();
+    let i = args.iter().position(|arg| arg == "--scene")?;
+    let path = args.get(i + 1).cloned();
+    if path.is_none() {
+        eprintln!("--scene espera la ruta de un archivo JSON; se usa la escena de ejemplo");
+    }
+    path
+}
+
+// `--threads N` limita el render a N hilos; sin el argumento se usan todos los núcleos
+fn thread_pool_from_args() -> Option<Arc<ThreadPool>> {
+    let args: Vec<String> = std::env::args().collect();
+    let i = args.iter().position(|arg| arg == "--threads")?;
+    match args.get(i + 1).and_then(|count| count.parse::<usize>().ok()).filter(|count| *count > 0) {
+        Some(threads) => thread_pool(threads)
+            .map_err(|e| eprintln!("No se pudo crear el pool de {} hilos: {}", threads, e))
+            .ok(),
+        None => {
+            eprintln!("--threads espera un número de hilos mayor que 0; se usan todos los núcleos");
+            None
         }
     }
+}
+
+// `--max-depth N` permite N rayos encadenados (el de cámara y sus reflejos o refracciones), por
+// ejemplo 4 o 5 para ver a través de varias capas de agua; sin el argumento se usa el valor por defecto
+fn max_depth_from_args() -> u32 {
+    let default = RenderConfig::default().max_depth;
+    let args: Vec<String> = std::env::args().collect();
+    let Some(i) = args.iter().position(|arg| arg == "--max-depth") else {
+        return default;
+    };
+    match args.get(i + 1).and_then(|depth| depth.parse::<u32>().ok()).filter(|depth| *depth > 0) {
+        Some(depth) => depth,
+        None => {
+            eprintln!("--max-depth espera un número mayor que 0; se usa {}", default);
+            default
+        }
+    }
+}
+
+// Líneas de la lectura del modo de colocación
+fn placement_lines(hit: Option<Pick>) -> Vec<String> {
+    match hit {
+        Some(hit) => vec![
+            format!("OBJ: {}", hit.object),
+            format!("POS: {:.2} {:.2} {:.2}", hit.point.x, hit.point.y, hit.point.z),
+            format!("NORMAL: {:.1} {:.1} {:.1}", hit.normal.x, hit.normal.y, hit.normal.z),
+        ],
+        None => vec!["SIN OBJETO".to_string()],
+    }
+}
+
+// Mete en una BVH todo lo que no se mueve, para que cada rayo no recorra los cientos de cubos. Los cubos
+// de agua se animan en cada cuadro y quedan fuera, donde el bucle principal los encuentra y los mueve
+// sin reconstruir el árbol.
+fn accelerate(objects: Vec<Box<dyn RayIntersect>>) -> Vec<Box<dyn RayIntersect>> {
+    let (water, fixed): (Vec<_>, Vec<_>) = objects
+        .into_iter()
+        .partition(|object| object.as_any().downcast_ref::<Cube>().is_some_and(|cube| cube.is_water));
+    let mut objects: Vec<Box<dyn RayIntersect>> = vec![Box::new(Bvh::new(fixed))];
+    objects.extend(water);
+    objects
+}
 
-    high_res_buffer
+// Título de la ventana con la exposición manual actual
+fn window_title(exposure: f32) -> String {
+    format!("Raytraced Cube - Exposición x{:.2}", exposure)
 }
 
+// Líneas de texto para la superposición de estadísticas
+fn stats_lines(fps: f32, stats: &RenderStats, resolution: (usize, usize)) -> Vec<String> {
+    let mut lines = vec![
+        format!("FPS: {:.1}", fps),
+        format!("RES: {}X{}", resolution.0, resolution.1),
+        format!("FRAME: {:.1} MS", stats.elapsed.as_secs_f64() * 1000.0),
+        format!("RAYS: {}", stats.rays),
+        format!("MRAYS/S: {:.2}", stats.rays_per_second() / 1e6),
+    ];
+    // El cuadro se cortó por el presupuesto de tiempo
+    if !stats.complete {
+        lines.push(format!("PARTIAL: {} PX", stats.pixels));
+    }
+    lines
+}
 }
\ No newline at end of file
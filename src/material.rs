@@ -1,5 +1,10 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+
 use crate::color::Color;
 use crate::texture::Texture;
+use crate::noise::ValueNoise;
+use nalgebra_glm::Vec3;
 
 #[derive(Debug, Clone)]  // Quitamos Copy, mantenemos Debug y Clone
 pub struct Material {
@@ -7,19 +12,235 @@ pub struct Material {
     pub specular: f32,
     pub albedo: [f32; 4],
     pub refractive_index: f32,
-    pub has_texture: bool,
-    pub texture: Option<Texture>,  // Textura opcional
+    pub texture: Option<Arc<Texture>>,  // Textura opcional, compartida entre materiales y teñida por `diffuse`
+    pub emission_texture: Option<Arc<Texture>>,  // Textura de emisión: los texeles no negros brillan por sí mismos
+    pub blend: Option<Box<MaskBlend>>,      // Mezcla opcional de dos materiales controlada por una máscara
+    pub reflection_color: Color,            // Tinte de lo reflejado; blanco deja el reflejo sin colorear
+    pub noise: Option<ValueNoise>,          // Ruido procedural opcional que rompe la repetición de la textura
+    pub thin_film: Option<ThinFilm>,        // Película delgada opcional (pompa de jabón, mancha de aceite) que irisa el reflejo
+    pub anisotropy: Option<Anisotropy>,     // Brillo especular estirado en una dirección (metal cepillado); None usa Phong
+    pub detail: Option<DetailLayer>,        // Textura de detalle a otra escala de UV que se combina con el color base
+    pub refraction_roughness: f32,          // Dispersión de la refracción en [0, 1] (vidrio esmerilado); 0 es vidrio claro
+    pub name: Option<Arc<str>>,             // Nombre en la biblioteca de la que salió; al guardar la escena se escribe solo el nombre
+}
+
+// Cómo se combina la textura de detalle con el color base. En ambos modos el gris medio (128) no
+// cambia nada: lo más claro aclara y lo más oscuro oscurece.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DetailBlend {
+    Multiply,  // Escala el color base (base * 2 * detalle)
+    Add,       // Suma la desviación del detalle respecto al gris medio
+}
+
+// Segunda textura muestreada con las UV multiplicadas por `scale` (por ejemplo, 8 para un grano fino
+// sobre la textura base), mezclada con peso `strength` en [0, 1]
+#[derive(Debug, Clone)]
+pub struct DetailLayer {
+    pub texture: Arc<Texture>,
+    pub scale: f32,
+    pub strength: f32,
+    pub mode: DetailBlend,
+}
+
+impl DetailLayer {
+    pub fn apply(&self, base: Color, u: f32, v: f32, footprint: f32) -> Color {
+        let detail = self.texture.sample_footprint(u * self.scale, v * self.scale, footprint * self.scale);
+        let combine = |base: u8, detail: u8| -> u8 {
+            let base = base as f32;
+            let detail = detail as f32 / 255.0;
+            let combined = match self.mode {
+                DetailBlend::Multiply => base * (1.0 + self.strength * (2.0 * detail - 1.0)),
+                DetailBlend::Add => base + self.strength * (detail - 0.5) * 255.0,
+            };
+            combined.clamp(0.0, 255.0) as u8
+        };
+        Color::new(combine(base.r, detail.r), combine(base.g, detail.g), combine(base.b, detail.b))
+    }
+}
+
+// Rugosidad distinta a lo largo de la tangente (u) y la bitangente (v): el brillo se alarga en
+// la dirección más rugosa, como en el metal cepillado
+#[derive(Debug, Clone, Copy)]
+pub struct Anisotropy {
+    pub roughness_u: f32,
+    pub roughness_v: f32,
+}
+
+impl Anisotropy {
+    // Intensidad especular tipo Ward (sin normalizar, con pico 1 como Phong) a partir de la normal,
+    // la tangente, la bitangente y las direcciones hacia la luz y hacia la cámara
+    pub fn specular(&self, normal: &Vec3, tangent: &Vec3, bitangent: &Vec3, light_dir: &Vec3, view_dir: &Vec3) -> f32 {
+        if normal.dot(light_dir) <= 0.0 || normal.dot(view_dir) <= 0.0 {
+            return 0.0;
+        }
+
+        let half = (light_dir + view_dir).normalize();
+        let h_n = half.dot(normal);
+        let h_u = half.dot(tangent) / self.roughness_u.max(1e-3);
+        let h_v = half.dot(bitangent) / self.roughness_v.max(1e-3);
+
+        let intensity = (-(h_u * h_u + h_v * h_v) / (h_n * h_n)).exp();
+        if intensity.is_finite() { intensity } else { 0.0 }
+    }
+}
+
+// Capa delgada sobre la superficie cuyas reflexiones interfieren entre sí: según el ángulo de vista,
+// unas longitudes de onda se refuerzan y otras se cancelan, y el reflejo se ve de colores
+#[derive(Debug, Clone, Copy)]
+pub struct ThinFilm {
+    pub thickness: f32,  // Grosor de la película en nanómetros (cientos de nm dan los colores más vivos)
+    pub ior: f32,        // Índice de refracción de la película
+}
+
+impl ThinFilm {
+    // Longitudes de onda representativas del rojo, el verde y el azul, en nanómetros
+    const WAVELENGTHS: [f32; 3] = [650.0, 510.0, 475.0];
+
+    // Factor por canal en [0, 1] para el reflejo, dado el coseno entre la normal y la dirección de vista
+    pub fn tint(&self, cos_incident: f32) -> [f32; 3] {
+        // Ángulo dentro de la película por la ley de Snell
+        let sin_incident_sq = 1.0 - cos_incident.clamp(0.0, 1.0).powi(2);
+        let cos_film = (1.0 - sin_incident_sq / (self.ior * self.ior)).max(0.0).sqrt();
+
+        // Diferencia de camino óptico entre la reflexión de arriba y la de abajo; el medio ciclo extra
+        // viene del cambio de fase al reflejarse en el medio más denso
+        let path_difference = 2.0 * self.ior * self.thickness * cos_film;
+        Self::WAVELENGTHS.map(|wavelength| {
+            let phase = 2.0 * std::f32::consts::PI * path_difference / wavelength + std::f32::consts::PI;
+            0.5 * (1.0 + phase.cos())
+        })
+    }
+}
+
+// Mezcla el color difuso de dos materiales según una máscara en escala de grises
+// (negro = base, blanco = overlay), útil para transiciones como grama -> arena. La máscara se
+// comparte como las demás texturas: se pide a `TextureCache` y se clona el `Arc`, no los píxeles.
+#[derive(Debug, Clone)]
+pub struct MaskBlend {
+    pub base: Material,
+    pub overlay: Material,
+    pub mask: Arc<Texture>,
 }
 
 impl Material {
-    pub fn get_diffuse_color(&self, u: f32, v: f32) -> Color {
-        if let Some(texture) = &self.texture {
-            let tex_x = (u * (texture.width as f32)) as usize % texture.width;
-            let tex_y = ((1.0 - v) * (texture.height as f32)) as usize % texture.height;
-            let pixel = texture.data[tex_y * texture.width + tex_x];
-            Color::new(pixel.r, pixel.g, pixel.b)
+    // Color difuso en (u, v); `point` es la posición en el mundo, usada por el ruido procedural, y
+    // `footprint` el ancho del rayo en unidades de UV, que elige el nivel de mip de las texturas
+    pub fn get_diffuse_color(&self, u: f32, v: f32, point: &Vec3, footprint: f32) -> Color {
+        let base = if let Some(blend) = &self.blend {
+            let weight = blend.mask.sample_footprint(u, v, footprint).rf();
+            let base = blend.base.get_diffuse_color(u, v, point, footprint);
+            let overlay = blend.overlay.get_diffuse_color(u, v, point, footprint);
+            base.lerp(&overlay, weight)
+        } else {
+            // Todo material se muestrea como texturizado: los de color plano leen la textura blanca y
+            // el color sale de `diffuse`
+            let texture = self.texture.as_deref().unwrap_or(Texture::white());
+            texture.sample_footprint(u, v, footprint).multiply(&self.diffuse)
+        };
+
+        let color = match &self.detail {
+            Some(detail) => detail.apply(base, u, v, footprint),
+            None => base,
+        };
+
+        match &self.noise {
+            Some(noise) => noise.modulate(color, point),
+            None => color,
+        }
+    }
+
+    // Color emitido en (u, v); negro si el material no tiene textura de emisión
+    pub fn get_emission_color(&self, u: f32, v: f32) -> Color {
+        match &self.emission_texture {
+            Some(texture) => texture.sample(u, v),
+            None => Color::new(0, 0, 0),
+        }
+    }
+
+    // Exponente especular válido: NaN o valores fuera de rango se llevan a [1, 1024]
+    pub fn specular_exponent(&self) -> f32 {
+        if self.specular.is_nan() {
+            1.0
         } else {
-            self.diffuse.clone()
+            self.specular.clamp(1.0, 1024.0)
+        }
+    }
+
+    #[inline]
+    pub fn is_reflective(&self) -> bool {
+        self.albedo[2] > 0.0
+    }
+
+    #[inline]
+    pub fn is_transparent(&self) -> bool {
+        self.albedo[3] > 0.0
+    }
+
+    #[inline]
+    pub fn is_emissive(&self) -> bool {
+        self.emission_texture.is_some()
+    }
+
+    // Vidrio claro: casi todo refractado, con un reflejo suave
+    pub fn glass() -> Self {
+        Material {
+            diffuse: Color::new(220, 235, 255),
+            specular: 125.0,
+            albedo: [0.05, 0.6, 0.15, 0.8],
+            refractive_index: 1.5,
+            ..Material::black()
+        }
+    }
+
+    // Espejo: refleja casi toda la luz sin teñirla
+    pub fn mirror() -> Self {
+        Material {
+            diffuse: Color::new(255, 255, 255),
+            specular: 1000.0,
+            albedo: [0.05, 0.5, 0.9, 0.0],
+            ..Material::black()
+        }
+    }
+
+    // Metal cromado: reflejo fuerte y algo azulado, con brillo especular intenso
+    pub fn chrome() -> Self {
+        Material {
+            diffuse: Color::new(200, 200, 210),
+            specular: 250.0,
+            albedo: [0.2, 0.8, 0.75, 0.0],
+            reflection_color: Color::new(220, 225, 235),
+            ..Material::black()
+        }
+    }
+
+    // Blanco mate: solo difuso, sin brillo ni reflejos
+    pub fn matte_white() -> Self {
+        Material {
+            diffuse: Color::new(240, 240, 240),
+            specular: 1.0,
+            albedo: [0.9, 0.0, 0.0, 0.0],
+            ..Material::black()
+        }
+    }
+
+    // Superficie que brilla en blanco por sí misma, sin importar la iluminación
+    pub fn emissive_white() -> Self {
+        Material {
+            emission_texture: Some(Arc::new(Texture::from_color(Color::new(255, 255, 255)))),
+            ..Material::matte_white()
+        }
+    }
+
+    // Agua clara: poco difusa, con reflejo algo verdoso y refracción con el índice del agua.
+    // Las fracciones difusa + reflejada + refractada suman 1.
+    pub fn water() -> Self {
+        Material {
+            diffuse: Color::new(30, 90, 130),
+            specular: 200.0,
+            albedo: [0.15, 0.6, 0.35, 0.5],
+            refractive_index: 1.33,
+            reflection_color: Color::new(200, 235, 240),
+            ..Material::black()
         }
     }
 
@@ -29,8 +250,60 @@ impl Material {
             specular: 0.0,
             albedo: [0.0, 0.0, 0.0, 0.0],
             refractive_index: 1.0,
-            has_texture: false,
             texture: None,
+            emission_texture: None,
+            blend: None,
+            reflection_color: Color::new(255, 255, 255),
+            noise: None,
+            thin_film: None,
+            anisotropy: None,
+            detail: None,
+            refraction_roughness: 0.0,
+            name: None,
         }
     }
 }
+
+
+// Materiales con nombre que una escena puede pedir sin repetir todos sus campos. `with_presets`
+// registra los incluidos: glass, water, mirror, matte_white, chrome y emissive_white.
+#[derive(Debug, Clone, Default)]
+pub struct MaterialLibrary {
+    materials: HashMap<String, Material>,
+}
+
+impl MaterialLibrary {
+    pub fn new() -> Self {
+        MaterialLibrary::default()
+    }
+
+    pub fn with_presets() -> Self {
+        let mut library = MaterialLibrary::new();
+        library.insert("glass", Material::glass());
+        library.insert("water", Material::water());
+        library.insert("mirror", Material::mirror());
+        library.insert("matte_white", Material::matte_white());
+        library.insert("chrome", Material::chrome());
+        library.insert("emissive_white", Material::emissive_white());
+        library
+    }
+
+    // Registra (o reemplaza) el material con ese nombre, que queda guardado en el propio material
+    pub fn insert(&mut self, name: &str, mut material: Material) {
+        material.name = Some(name.into());
+        self.materials.insert(name.to_string(), material);
+    }
+
+    pub fn get(&self, name: &str) -> Option<&Material> {
+        self.materials.get(name)
+    }
+
+    // Copia del material para una instancia; sus campos se pueden cambiar sin afectar a la biblioteca
+    pub fn instance(&self, name: &str) -> Option<Material> {
+        self.get(name).cloned()
+    }
+
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.materials.keys().map(String::as_str)
+    }
+}
@@ -0,0 +1,126 @@
+use std::fmt;
+
+use nalgebra_glm::Vec3;
+
+use crate::intersect::RayIntersect;
+use crate::material::Material;
+use crate::triangle::Triangle;
+
+// Error al leer un archivo OBJ; `line` empieza en 1, como en un editor
+#[derive(Debug)]
+pub enum MeshError {
+    Io { path: String, error: std::io::Error },
+    // Número mal escrito o registro con menos valores de los necesarios
+    Parse { path: String, line: usize, message: String },
+    // Índice de vértice, normal o UV que no existe (o 0, que OBJ no usa)
+    BadIndex { path: String, line: usize, index: String },
+}
+
+impl fmt::Display for MeshError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            MeshError::Io { path, error } => write!(f, "{}: {}", path, error),
+            MeshError::Parse { path, line, message } => write!(f, "{}:{}: {}", path, line, message),
+            MeshError::BadIndex { path, line, index } => write!(f, "{}:{}: index \"{}\" out of range", path, line, index),
+        }
+    }
+}
+
+impl std::error::Error for MeshError {}
+
+// Un vértice de una cara: índices (desde 0) a posición y, si los hay, a UV y normal
+type Corner = (usize, Option<usize>, Option<usize>);
+
+// Carga los triángulos de un archivo OBJ con `material` para todos. Lee `v`, `vt`, `vn` y `f` (en las
+// formas v, v/vt, v//vn y v/vt/vn, con índices desde 1 o negativos contando desde el final) e ignora el
+// resto de registros. Las caras de más de tres vértices se dividen en abanico desde el primero.
+pub fn load_obj(path: &str, material: Material) -> Result<Vec<Box<dyn RayIntersect>>, MeshError> {
+    let text = std::fs::read_to_string(path).map_err(|error| MeshError::Io { path: path.to_string(), error })?;
+
+    let mut positions: Vec<Vec3> = Vec::new();
+    let mut uvs: Vec<(f32, f32)> = Vec::new();
+    let mut normals: Vec<Vec3> = Vec::new();
+    let mut triangles: Vec<Box<dyn RayIntersect>> = Vec::new();
+
+    for (i, line) in text.lines().enumerate() {
+        let line_number = i + 1;
+        let parse_error = |message: String| MeshError::Parse { path: path.to_string(), line: line_number, message };
+        let mut fields = line.split_whitespace();
+
+        match fields.next() {
+            Some("v") => {
+                let [x, y, z] = parse_floats(&mut fields).map_err(parse_error)?;
+                positions.push(Vec3::new(x, y, z));
+            }
+            Some("vt") => {
+                let [u, v] = parse_floats(&mut fields).map_err(parse_error)?;
+                uvs.push((u, v));
+            }
+            Some("vn") => {
+                let [x, y, z] = parse_floats(&mut fields).map_err(parse_error)?;
+                normals.push(Vec3::new(x, y, z).normalize());
+            }
+            Some("f") => {
+                let corners = fields
+                    .map(|field| {
+                        parse_corner(field, positions.len(), uvs.len(), normals.len()).ok_or_else(|| MeshError::BadIndex {
+                            path: path.to_string(),
+                            line: line_number,
+                            index: field.to_string(),
+                        })
+                    })
+                    .collect::<Result<Vec<Corner>, MeshError>>()?;
+                if corners.len() < 3 {
+                    return Err(parse_error(format!("face with {} vertices", corners.len())));
+                }
+
+                for k in 1..corners.len() - 1 {
+                    let face = [corners[0], corners[k], corners[k + 1]];
+                    let mut triangle = Triangle::new(face.map(|(v, _, _)| positions[v]), material.clone());
+                    // Las normales y UV solo se usan si las tienen los tres vértices
+                    if let [(_, Some(a), _), (_, Some(b), _), (_, Some(c), _)] = face {
+                        triangle.uvs = Some([uvs[a], uvs[b], uvs[c]]);
+                    }
+                    if let [(_, _, Some(a)), (_, _, Some(b)), (_, _, Some(c))] = face {
+                        triangle.normals = Some([normals[a], normals[b], normals[c]]);
+                    }
+                    triangles.push(Box::new(triangle));
+                }
+            }
+            _ => {}
+        }
+    }
+
+    Ok(triangles)
+}
+
+// Los primeros N números del registro; los que sobran (como la w opcional de `v`) se ignoran
+fn parse_floats<'a, const N: usize>(fields: &mut impl Iterator<Item = &'a str>) -> Result<[f32; N], String> {
+    let mut values = [0.0; N];
+    for value in values.iter_mut() {
+        let field = fields.next().ok_or_else(|| format!("expected {} numbers", N))?;
+        *value = field.parse().map_err(|_| format!("invalid number \"{}\"", field))?;
+    }
+    Ok(values)
+}
+
+// Índice OBJ (desde 1, o negativo desde el final) a índice desde 0; None si está fuera de rango
+fn resolve_index(field: &str, count: usize) -> Option<usize> {
+    let index: i64 = field.parse().ok()?;
+    let resolved = if index > 0 { index - 1 } else { count as i64 + index };
+    (index != 0 && resolved >= 0 && resolved < count as i64).then_some(resolved as usize)
+}
+
+fn parse_corner(field: &str, positions: usize, uvs: usize, normals: usize) -> Option<Corner> {
+    let mut parts = field.split('/');
+    let position = resolve_index(parts.next()?, positions)?;
+    let uv = match parts.next() {
+        None | Some("") => None,
+        Some(part) => Some(resolve_index(part, uvs)?),
+    };
+    let normal = match parts.next() {
+        None | Some("") => None,
+        Some(part) => Some(resolve_index(part, normals)?),
+    };
+    Some((position, uv, normal))
+}
@@ -0,0 +1,62 @@
+use nalgebra_glm::Vec3;
+
+use crate::color::Color;
+
+// Ruido de valor 3D determinista: cada vértice de la rejilla entera recibe un valor pseudoaleatorio
+// derivado de la semilla y se interpola suavemente entre ellos.
+#[derive(Debug, Clone, Copy)]
+pub struct ValueNoise {
+    pub seed: u32,
+    pub frequency: f32,  // Celdas de ruido por unidad de mundo
+    pub strength: f32,   // Variación máxima de brillo, en [0, 1]
+}
+
+impl ValueNoise {
+    pub fn new(seed: u32, frequency: f32, strength: f32) -> Self {
+        ValueNoise { seed, frequency, strength }
+    }
+
+    // Valor del ruido en un punto del mundo, en el rango [0, 1]
+    pub fn value(&self, point: &Vec3) -> f32 {
+        let p = point * self.frequency;
+        let (x0, y0, z0) = (p.x.floor(), p.y.floor(), p.z.floor());
+        let (tx, ty, tz) = (smoothstep(p.x - x0), smoothstep(p.y - y0), smoothstep(p.z - z0));
+        let (x0, y0, z0) = (x0 as i32, y0 as i32, z0 as i32);
+
+        let corner = |dx: i32, dy: i32, dz: i32| self.lattice(x0 + dx, y0 + dy, z0 + dz);
+        let lerp = |a: f32, b: f32, t: f32| a + (b - a) * t;
+
+        let x00 = lerp(corner(0, 0, 0), corner(1, 0, 0), tx);
+        let x10 = lerp(corner(0, 1, 0), corner(1, 1, 0), tx);
+        let x01 = lerp(corner(0, 0, 1), corner(1, 0, 1), tx);
+        let x11 = lerp(corner(0, 1, 1), corner(1, 1, 1), tx);
+
+        lerp(lerp(x00, x10, ty), lerp(x01, x11, ty), tz)
+    }
+
+    // Aclara u oscurece el color según el ruido en el punto, sin cambiar su tono
+    pub fn modulate(&self, color: Color, point: &Vec3) -> Color {
+        let factor = 1.0 + self.strength * (self.value(point) * 2.0 - 1.0);
+        Color {
+            r: (color.r as f32 * factor).clamp(0.0, 255.0) as u8,
+            g: (color.g as f32 * factor).clamp(0.0, 255.0) as u8,
+            b: (color.b as f32 * factor).clamp(0.0, 255.0) as u8,
+        }
+    }
+
+    // Valor pseudoaleatorio en [0, 1] para un vértice de la rejilla
+    fn lattice(&self, x: i32, y: i32, z: i32) -> f32 {
+        let mut h = self.seed
+            ^ (x as u32).wrapping_mul(0x8da6_b343)
+            ^ (y as u32).wrapping_mul(0xd816_3841)
+            ^ (z as u32).wrapping_mul(0xcb1a_b31f);
+        h = (h ^ (h >> 16)).wrapping_mul(0x7feb_352d);
+        h = (h ^ (h >> 15)).wrapping_mul(0x846c_a68b);
+        h ^= h >> 16;
+        h as f32 / u32::MAX as f32
+    }
+}
+
+fn smoothstep(t: f32) -> f32 {
+    t * t * (3.0 - 2.0 * t)
+}
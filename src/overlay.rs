@@ -0,0 +1,131 @@
+// Texto de superposición dibujado directamente en el framebuffer con una fuente de 3x5 píxeles
+
+const GLYPH_WIDTH: usize = 3;
+const GLYPH_HEIGHT: usize = 5;
+
+// Cada fila usa 3 bits; el bit más alto es la columna izquierda
+fn glyph(character: char) -> [u8; GLYPH_HEIGHT] {
+    match character.to_ascii_uppercase() {
+        '0' => [0b111, 0b101, 0b101, 0b101, 0b111],
+        '1' => [0b010, 0b110, 0b010, 0b010, 0b111],
+        '2' => [0b111, 0b001, 0b111, 0b100, 0b111],
+        '3' => [0b111, 0b001, 0b111, 0b001, 0b111],
+        '4' => [0b101, 0b101, 0b111, 0b001, 0b001],
+        '5' => [0b111, 0b100, 0b111, 0b001, 0b111],
+        '6' => [0b111, 0b100, 0b111, 0b101, 0b111],
+        '7' => [0b111, 0b001, 0b001, 0b001, 0b001],
+        '8' => [0b111, 0b101, 0b111, 0b101, 0b111],
+        '9' => [0b111, 0b101, 0b111, 0b001, 0b111],
+        'A' => [0b010, 0b101, 0b111, 0b101, 0b101],
+        'B' => [0b110, 0b101, 0b110, 0b101, 0b110],
+        'C' => [0b011, 0b100, 0b100, 0b100, 0b011],
+        'D' => [0b110, 0b101, 0b101, 0b101, 0b110],
+        'E' => [0b111, 0b100, 0b110, 0b100, 0b111],
+        'F' => [0b111, 0b100, 0b110, 0b100, 0b100],
+        'G' => [0b011, 0b100, 0b101, 0b101, 0b011],
+        'H' => [0b101, 0b101, 0b111, 0b101, 0b101],
+        'I' => [0b111, 0b010, 0b010, 0b010, 0b111],
+        'J' => [0b001, 0b001, 0b001, 0b101, 0b010],
+        'K' => [0b101, 0b101, 0b110, 0b101, 0b101],
+        'L' => [0b100, 0b100, 0b100, 0b100, 0b111],
+        'M' => [0b101, 0b111, 0b111, 0b101, 0b101],
+        'N' => [0b110, 0b101, 0b101, 0b101, 0b101],
+        'O' => [0b010, 0b101, 0b101, 0b101, 0b010],
+        'P' => [0b110, 0b101, 0b110, 0b100, 0b100],
+        'Q' => [0b010, 0b101, 0b101, 0b110, 0b011],
+        'R' => [0b110, 0b101, 0b110, 0b101, 0b101],
+        'S' => [0b011, 0b100, 0b010, 0b001, 0b110],
+        'T' => [0b111, 0b010, 0b010, 0b010, 0b010],
+        'U' => [0b101, 0b101, 0b101, 0b101, 0b111],
+        'V' => [0b101, 0b101, 0b101, 0b101, 0b010],
+        'W' => [0b101, 0b101, 0b111, 0b111, 0b101],
+        'X' => [0b101, 0b101, 0b010, 0b101, 0b101],
+        'Y' => [0b101, 0b101, 0b010, 0b010, 0b010],
+        'Z' => [0b111, 0b001, 0b010, 0b100, 0b111],
+        ':' => [0b000, 0b010, 0b000, 0b010, 0b000],
+        '.' => [0b000, 0b000, 0b000, 0b000, 0b010],
+        '/' => [0b001, 0b001, 0b010, 0b100, 0b100],
+        '-' => [0b000, 0b000, 0b111, 0b000, 0b000],
+        '+' => [0b000, 0b010, 0b111, 0b010, 0b000],
+        '%' => [0b101, 0b001, 0b010, 0b100, 0b101],
+        _ => [0b000; GLYPH_HEIGHT],
+    }
+}
+
+// Dibuja `text` con su esquina superior izquierda en `position`; cada píxel de la fuente ocupa scale x scale
+pub fn draw_text(
+    framebuffer: &mut [u32],
+    width: usize,
+    height: usize,
+    position: (usize, usize),
+    text: &str,
+    color: u32,
+    scale: usize,
+) {
+    let (x, y) = position;
+    for (i, character) in text.chars().enumerate() {
+        let origin_x = x + i * (GLYPH_WIDTH + 1) * scale;
+
+        for (row, bits) in glyph(character).iter().enumerate() {
+            for column in 0..GLYPH_WIDTH {
+                if bits & (1 << (GLYPH_WIDTH - 1 - column)) == 0 {
+                    continue;
+                }
+                fill_rect(
+                    framebuffer,
+                    width,
+                    height,
+                    (origin_x + column * scale, y + row * scale, scale, scale),
+                    color,
+                );
+            }
+        }
+    }
+}
+
+// Rellena el rectángulo (x, y, ancho, alto), recortado a los bordes del framebuffer
+pub fn fill_rect(
+    framebuffer: &mut [u32],
+    width: usize,
+    height: usize,
+    rect: (usize, usize, usize, usize),
+    color: u32,
+) {
+    let (x, y, rect_width, rect_height) = rect;
+    for py in y..(y + rect_height).min(height) {
+        for px in x..(x + rect_width).min(width) {
+            framebuffer[py * width + px] = color;
+        }
+    }
+}
+
+// Panel de varias líneas en la esquina superior izquierda, con fondo oscuro para que se lea sobre la escena
+pub fn draw_text_panel(framebuffer: &mut [u32], width: usize, height: usize, lines: &[String]) {
+    draw_panel(framebuffer, width, height, lines, false);
+}
+
+// Igual que `draw_text_panel`, pero en la esquina inferior izquierda
+pub fn draw_text_panel_bottom(framebuffer: &mut [u32], width: usize, height: usize, lines: &[String]) {
+    draw_panel(framebuffer, width, height, lines, true);
+}
+
+fn draw_panel(framebuffer: &mut [u32], width: usize, height: usize, lines: &[String], at_bottom: bool) {
+    let scale = 2;
+    let margin = 4;
+    let line_height = (GLYPH_HEIGHT + 2) * scale;
+    let longest = lines.iter().map(|line| line.chars().count()).max().unwrap_or(0);
+    let panel_height = lines.len() * line_height + 2 * margin;
+    let top = if at_bottom { height.saturating_sub(panel_height) } else { 0 };
+
+    fill_rect(
+        framebuffer,
+        width,
+        height,
+        (0, top, longest * (GLYPH_WIDTH + 1) * scale + 2 * margin, panel_height),
+        0x000000,
+    );
+
+    for (i, line) in lines.iter().enumerate() {
+        draw_text(framebuffer, width, height, (margin, top + margin + i * line_height), line, 0xFFFFFF, scale);
+    }
+}
@@ -0,0 +1,77 @@
+use std::any::Any;
+
+use nalgebra_glm::Vec3;
+use crate::intersect::{Intersect, RayIntersect};
+use crate::material::Material;
+use crate::aabb::Aabb;
+use crate::sampling::orthonormal_basis;
+
+// Lado de cada repetición de la textura sobre el plano, igual al lado de los cubos de la escena
+const PLANE_TILE_SIZE: f32 = 2.0;
+
+// Plano infinito que pasa por `point` con la normal dada (hacia el lado "de afuera")
+pub struct Plane {
+    pub point: Vec3,
+    pub normal: Vec3,
+    pub material: Material,
+}
+
+impl Plane {
+    pub fn new(point: Vec3, normal: Vec3, material: Material) -> Self {
+        Plane { point, normal: normal.normalize(), material }
+    }
+
+    // Distancia donde el rayo cruza el plano, o None si es paralelo a él
+    fn hit_distance(&self, ray_origin: &Vec3, ray_direction: &Vec3) -> Option<f32> {
+        let denominator = ray_direction.dot(&self.normal);
+        if denominator.abs() < 1e-6 {
+            return None;
+        }
+        Some((self.point - ray_origin).dot(&self.normal) / denominator)
+    }
+
+    // La textura se repite cada PLANE_TILE_SIZE unidades a lo largo de la tangente y la bitangente
+    fn get_uv(&self, point: &Vec3) -> (f32, f32) {
+        let (tangent, bitangent) = orthonormal_basis(&self.normal);
+        let offset = point - self.point;
+        (
+            (offset.dot(&tangent) / PLANE_TILE_SIZE).rem_euclid(1.0),
+            (offset.dot(&bitangent) / PLANE_TILE_SIZE).rem_euclid(1.0),
+        )
+    }
+}
+
+impl RayIntersect for Plane {
+    fn ray_intersect(&self, ray_origin: &Vec3, ray_direction: &Vec3, t_min: f32, t_max: f32) -> Intersect {
+        let t_hit = match self.hit_distance(ray_origin, ray_direction) {
+            Some(t) if t >= t_min && t <= t_max => t,
+            _ => return Intersect::empty(),
+        };
+
+        let point = ray_origin + ray_direction * t_hit;
+        let (u, v) = self.get_uv(&point);
+        // El rayo llega desde afuera si viaja en contra de la normal
+        let front_face = ray_direction.dot(&self.normal) < 0.0;
+
+        Intersect::new(point, self.normal, t_hit, self.material.clone(), u, v, front_face).with_uv_scale(1.0 / PLANE_TILE_SIZE)
+    }
+
+    fn intersect_any(&self, ray_origin: &Vec3, ray_direction: &Vec3, t_min: f32, t_max: f32) -> Option<f32> {
+        self.hit_distance(ray_origin, ray_direction).filter(|t| *t >= t_min && *t <= t_max)
+    }
+
+    // La caja es infinita en los ejes que el plano recorre; solo un plano alineado a los ejes tiene
+    // un eje acotado
+    fn bounding_box(&self) -> Aabb {
+        let extent = self.normal.map(|n| if n.abs() > 1.0 - 1e-6 { 0.0 } else { f32::INFINITY });
+        Aabb::new(self.point - extent, self.point + extent)
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        self
+    }
+}
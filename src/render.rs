@@ -0,0 +1,1172 @@
+use std::cell::Cell;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use nalgebra_glm::Vec3;
+use rayon::prelude::*;
+use rayon::ThreadPool;
+
+use crate::background::Background;
+use crate::bvh::Bvh;
+use crate::camera::Camera;
+use crate::color;
+use crate::intersect::{Intersect, RayIntersect};
+use crate::light::{Light, LightShape};
+use crate::sampling::{hash, orthonormal_basis, pixel_jitter, sample_hemisphere_cosine};
+use crate::scene::Scene;
+use crate::gbuffer::GBuffer;
+use crate::denoise::denoise_atrous;
+
+
+// Distancia mínima para rayos secundarios, evita que choquen con la superficie de la que salen
+pub const RAY_EPSILON: f32 = 1e-3;
+
+thread_local! {
+    // Rayos lanzados por el hilo actual; `render` suma la diferencia de cada bloque en sus estadísticas
+    static RAYS_CAST: Cell<u64> = const { Cell::new(0) };
+}
+
+fn count_ray() {
+    RAYS_CAST.with(|count| count.set(count.get() + 1));
+}
+
+fn rays_cast() -> u64 {
+    RAYS_CAST.with(|count| count.get())
+}
+
+
+pub fn reflect(incident: &Vec3, normal: &Vec3) -> Vec3 {
+    incident - 2.0 * incident.dot(normal) * normal
+}
+
+// Refracta `incident` al cruzar una superficie entre el medio exterior (eta_outside) y el
+// interior del objeto (eta_inside). La normal apunta hacia afuera del objeto y `front_face`
+// indica si el rayo entra al objeto (true) o sale de él (false), según lo reportó la intersección.
+pub fn refract(incident: &Vec3, normal: &Vec3, front_face: bool, eta_outside: f32, eta_inside: f32) -> Vec3 {
+    // Trabajamos con la normal del lado por donde llega el rayo
+    let (eta, n_normal) = if front_face {
+        (eta_outside / eta_inside, *normal)
+    } else {
+        (eta_inside / eta_outside, -*normal)
+    };
+    let n_cosi = -incident.dot(&n_normal).clamp(-1.0, 1.0);
+
+    let k = 1.0 - eta * eta * (1.0 - n_cosi * n_cosi);
+
+    if k < 0.0 {
+        // Total internal reflection, return reflected ray
+        reflect(incident, &n_normal)
+    } else {
+        eta * incident + (eta * n_cosi - k.sqrt()) * n_normal
+    }
+}
+
+
+// Potencia para el brillo especular; los exponentes enteros (el caso común) usan powi, que es más barato
+pub fn specular_power(cos_angle: f32, exponent: f32) -> f32 {
+    let base = cos_angle.clamp(0.0, 1.0);
+    let intensity = if exponent.fract() == 0.0 {
+        base.powi(exponent as i32)
+    } else {
+        base.powf(exponent)
+    };
+
+    // Evita que un NaN llegue al framebuffer como un píxel saturado
+    if intensity.is_finite() { intensity } else { 0.0 }
+}
+
+
+// Número de rayos para la búsqueda de bloqueadores y para el filtrado de las sombras suaves
+const SOFT_SHADOW_SAMPLES: usize = 8;
+
+// Desplazamientos en el disco unitario (espiral de Vogel), fijos para que la sombra no tenga ruido
+fn disk_sample(index: usize) -> (f32, f32) {
+    let golden_angle = std::f32::consts::PI * (3.0 - 5.0f32.sqrt());
+    let radius = ((index as f32 + 0.5) / SOFT_SHADOW_SAMPLES as f32).sqrt();
+    let angle = index as f32 * golden_angle;
+    (radius * angle.cos(), radius * angle.sin())
+}
+
+// Desplazamiento desde el centro de la luz hasta su punto de muestra número `index`, según su forma.
+// `light_dir` va del receptor a la luz: una esfera se ve como un disco perpendicular a esa dirección.
+// Un disco con normal nula (o no finita) no tiene orientación y se trata como una esfera.
+fn light_sample_offset(light: &Light, light_dir: &Vec3, index: usize) -> Vec3 {
+    let (du, dv) = disk_sample(index);
+    let (u, v) = match light.shape {
+        LightShape::Disk { normal } if normal.norm() > 0.0 && normal.norm().is_finite() => orthonormal_basis(&normal.normalize()),
+        _ => orthonormal_basis(light_dir),
+    };
+    (u * du + v * dv) * light.radius
+}
+
+// Lanza un rayo de sombra desde `origin` hacia `target`: devuelve la distancia al primer objeto que
+// proyecta sombra en el camino y la distancia total, o None si el camino está libre
+fn shadow_blocker(origin: &Vec3, target: &Vec3, objects: &[Box<dyn RayIntersect>]) -> Option<(f32, f32)> {
+    count_ray();
+
+    let to_target = target - origin;
+    let distance = to_target.magnitude();
+    let direction = to_target / distance;
+
+    // Aceptamos solo impactos entre el punto y la luz
+    objects
+        .iter()
+        .filter(|object| object.casts_shadow())
+        .find_map(|object| object.intersect_any(origin, &direction, RAY_EPSILON, distance))
+        .map(|blocker_distance| (blocker_distance, distance))
+}
+
+// Intensidad de la sombra de un solo rayo, que se debilita cuanto más cerca de la luz está el bloqueador
+fn shadow_ray(origin: &Vec3, target: &Vec3, objects: &[Box<dyn RayIntersect>]) -> f32 {
+    match shadow_blocker(origin, target, objects) {
+        Some((blocker_distance, distance)) => 1.0 - (blocker_distance / distance).min(1.0),
+        None => 0.0,
+    }
+}
+
+pub fn cast_shadow(
+    intersect: &Intersect,
+    light: &Light,
+    objects: &[Box<dyn RayIntersect>],
+) -> f32 {
+    // Luz puntual: un solo rayo y sombra dura
+    if light.radius <= 0.0 {
+        return shadow_ray(&intersect.point, &light.position, objects);
+    }
+
+    // Luz de área (PCSS): primero buscamos bloqueadores con rayos hacia varios puntos de la luz
+    let light_dir = (light.position - intersect.point).normalize();
+    let distance_to_light = (light.position - intersect.point).magnitude();
+
+    let blockers: Vec<f32> = (0..SOFT_SHADOW_SAMPLES)
+        .filter_map(|i| {
+            let target = light.position + light_sample_offset(light, &light_dir, i);
+            shadow_blocker(&intersect.point, &target, objects).map(|(blocker_distance, _)| blocker_distance)
+        })
+        .collect();
+    if blockers.is_empty() {
+        return 0.0;
+    }
+
+    // La penumbra crece con la distancia entre el bloqueador y el receptor: en el punto de contacto
+    // la sombra es dura y se suaviza a medida que el bloqueador se aleja
+    let blocker_distance = blockers.iter().sum::<f32>() / blockers.len() as f32;
+    let blocker_to_light = (distance_to_light - blocker_distance).max(RAY_EPSILON);
+    let penumbra_scale = blocker_distance / blocker_to_light;
+
+    // Filtramos la sombra promediando rayos desde puntos vecinos del receptor: la forma de la luz
+    // proyectada a través del bloqueador (invertida), aplanada sobre la superficie
+    let total: f32 = (0..SOFT_SHADOW_SAMPLES)
+        .map(|i| {
+            let offset = -light_sample_offset(light, &light_dir, i) * penumbra_scale;
+            let origin = intersect.point + offset - intersect.normal * offset.dot(&intersect.normal);
+            shadow_ray(&origin, &light.position, objects)
+        })
+        .sum();
+
+    total / SOFT_SHADOW_SAMPLES as f32
+}
+
+
+// Color de un rayo que no choca con nada: los de cámara (profundidad 0) ven `background` y los
+// secundarios ven `environment`, para poder tener, por ejemplo, un fondo liso con reflejos del cielo
+fn miss_color(ray_direction: &Vec3, config: &RenderConfig, depth: u32) -> color::Color {
+    match &config.environment {
+        Some(environment) if depth > 0 => environment.sample(ray_direction),
+        _ => config.background.sample(ray_direction),
+    }
+}
+
+// Posición de un rayo en la recursión: cuántos rebotes lleva y qué fracción del color del píxel
+// aporta (el producto de las reflectividades y transparencias por las que pasó)
+#[derive(Debug, Clone, Copy)]
+struct Bounce {
+    depth: u32,
+    throughput: f32,
+    skip_refraction: bool,  // La capa se sombrea sin lo que hay detrás, que compone quien la llama (depth peeling)
+    cone: RayCone,
+}
+
+impl Bounce {
+    fn primary(depth: u32) -> Bounce {
+        Bounce { depth, throughput: 1.0, skip_refraction: false, cone: RayCone::fallback() }
+    }
+
+    // El rayo secundario que sale con peso `weight` (reflectividad o transparencia de la superficie)
+    fn next(self, weight: f32) -> Bounce {
+        Bounce { depth: self.depth + 1, throughput: self.throughput * weight, skip_refraction: false, cone: self.cone }
+    }
+}
+
+// Cono que envuelve los rayos de un píxel: su ancho en el origen y cuánto crece por unidad de
+// distancia. Al chocar da el ancho que cubre el rayo sobre la superficie (su huella), con el que se
+// elige el nivel de mip de las texturas.
+#[derive(Debug, Clone, Copy)]
+struct RayCone {
+    width: f32,
+    spread: f32,
+}
+
+// Coseno mínimo entre el rayo y la normal al estirar la huella: de lado la huella crece hasta 20 veces
+const MIN_FOOTPRINT_COS: f32 = 0.05;
+
+// Apertura del cono de los rayos que no salen de un píxel (`cast_ray`): la de un píxel de una imagen
+// de 600 filas con el fov por defecto, así su nivel de mip depende solo de la distancia
+const FALLBACK_CONE_SPREAD: f32 = 2.0 / 600.0;
+
+impl RayCone {
+    // El cono de un píxel de una imagen de `height` filas, vista con `camera` y `projection`
+    fn pixel(camera: &Camera, projection: Projection, height: usize) -> RayCone {
+        match projection {
+            Projection::Perspective => RayCone { width: 0.0, spread: 2.0 * camera.half_height() / height as f32 },
+            Projection::Orthographic { view_height } => RayCone { width: view_height / height as f32, spread: 0.0 },
+        }
+    }
+
+    fn fallback() -> RayCone {
+        RayCone { width: 0.0, spread: FALLBACK_CONE_SPREAD }
+    }
+
+    fn width_at(&self, distance: f32) -> f32 {
+        self.width + self.spread * distance
+    }
+}
+
+// Partes que forman el color de un rayo, por separado (AOVs), para depurar por qué un píxel tiene
+// cierto color o componer cada capa aparte. Cada parte ya lleva su peso en el píxel (la difusa por
+// `1 - reflectividad - transparencia`, el reflejo por la reflectividad, etc.), así que sumarlas da
+// `color` salvo por los recortes a [0, 255]. Las luces sustractivas restan de la parte a la que
+// aportan. `shadow` no suma: es la luz directa que bloquearon las sombras.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ShadingBreakdown {
+    pub color: color::Color,       // El color completo, el mismo que devuelve `cast_ray`
+    pub background: color::Color,  // Fondo visto por el rayo si no chocó con nada
+    pub ambient: color::Color,
+    pub diffuse: color::Color,
+    pub specular: color::Color,
+    pub shadow: color::Color,
+    pub reflection: color::Color,
+    pub refraction: color::Color,
+    pub emission: color::Color,
+}
+
+impl ShadingBreakdown {
+    // Un rayo que no chocó con nada: todo su color es fondo
+    fn miss(color: color::Color) -> Self {
+        ShadingBreakdown { color, background: color, ..ShadingBreakdown::default() }
+    }
+
+    // Un color que no se descompone (las vistas de depuración)
+    fn flat(color: color::Color) -> Self {
+        ShadingBreakdown { color, ..ShadingBreakdown::default() }
+    }
+}
+
+pub fn cast_ray(
+    ray_origin: &Vec3,
+    ray_direction: &Vec3,
+    objects: &[Box<dyn RayIntersect>],
+    lights: &[Light],
+    config: &RenderConfig,
+    depth: u32,
+) -> color::Color {
+    trace_ray::<false>(ray_origin, ray_direction, objects, lights, config, Bounce::primary(depth)).color
+}
+
+// Rayo de cámara con el cono de un píxel de una imagen de `height` filas, igual que los de `render`.
+// `BREAKDOWN` funciona como en `trace_ray`: sin él solo se llena `color`.
+pub(crate) fn trace_camera_ray<const BREAKDOWN: bool>(
+    ray_origin: &Vec3,
+    ray_direction: &Vec3,
+    objects: &[Box<dyn RayIntersect>],
+    lights: &[Light],
+    camera: &Camera,
+    config: &RenderConfig,
+    height: usize,
+) -> ShadingBreakdown {
+    let bounce = Bounce { cone: RayCone::pixel(camera, config.projection, height), ..Bounce::primary(0) };
+    trace_ray::<BREAKDOWN>(ray_origin, ray_direction, objects, lights, config, bounce)
+}
+
+// Igual que `cast_ray`, pero devuelve también cada parte del sombreado del primer impacto. Los rayos
+// reflejados y refractados aportan su color completo a `reflection` y `refraction`.
+pub fn cast_ray_breakdown(
+    ray_origin: &Vec3,
+    ray_direction: &Vec3,
+    objects: &[Box<dyn RayIntersect>],
+    lights: &[Light],
+    config: &RenderConfig,
+    depth: u32,
+) -> ShadingBreakdown {
+    trace_ray::<true>(ray_origin, ray_direction, objects, lights, config, Bounce::primary(depth))
+}
+
+// Sigue un rayo y devuelve su color. Con `BREAKDOWN` también separa las partes del sombreado del
+// primer impacto; sin él solo se llena `color`, y el compilador quita las sumas por parte del camino
+// de `cast_ray` y del render. Los rayos secundarios nunca las necesitan.
+fn trace_ray<const BREAKDOWN: bool>(
+    ray_origin: &Vec3,
+    ray_direction: &Vec3,
+    objects: &[Box<dyn RayIntersect>],
+    lights: &[Light],
+    config: &RenderConfig,
+    bounce: Bounce,
+) -> ShadingBreakdown {
+    count_ray();
+    let depth = bounce.depth;
+
+    // Pasado el límite de rebotes el rayo ve el fondo, sin buscar más impactos
+    if depth >= config.max_depth {
+        return ShadingBreakdown::miss(miss_color(ray_direction, config, depth));  // Color de fondo
+    }
+
+    let mut closest_intersection = Intersect::empty();
+    let mut closest_distance = f32::INFINITY;
+
+    // Ignoramos impactos demasiado cercanos: los rayos secundarios salen de una superficie y la cámara
+    // libre puede quedar justo sobre una cara, lo que haría que el rayo se intersecte consigo mismo.
+    // Buscar la intersección más cercana con cualquier objeto
+    for object in objects {
+        let intersection = object.ray_intersect(ray_origin, ray_direction, RAY_EPSILON, closest_distance);
+        if intersection.is_intersecting {
+            closest_distance = intersection.distance;
+            closest_intersection = intersection;
+        }
+    }
+
+    if !closest_intersection.is_intersecting {
+        return ShadingBreakdown::miss(miss_color(ray_direction, config, depth));  // Color del cielo o fondo
+    }
+
+    // Vista de depuración: la normal se muestra como color, (n * 0.5 + 0.5) en cada canal
+    if config.debug_view == DebugView::Normals {
+        let normal = closest_intersection.normal.normalize();
+        return ShadingBreakdown::flat(color::Color::from_normalized(normal.x * 0.5 + 0.5, normal.y * 0.5 + 0.5, normal.z * 0.5 + 0.5));
+    }
+
+    // Vista de depuración: (u, v) como rojo y verde, para ver costuras y orientación de la textura
+    if config.debug_view == DebugView::Uv {
+        let (u, v) = (closest_intersection.u, closest_intersection.v);
+        return ShadingBreakdown::flat(color::Color::from_normalized(u, v, 0.0));
+    }
+
+    // Las superficies opacas se sombrean por la cara que ve el rayo: al pegarle por detrás a un plano o
+    // a un triángulo se usa la normal invertida, así la luz de ese lado ilumina y proyecta sombra. Las
+    // transparentes conservan la normal de la cara, porque `refract` usa `front_face` para saber si el
+    // rayo entra o sale.
+    if !closest_intersection.front_face && !closest_intersection.material.is_transparent() {
+        closest_intersection.normal = -closest_intersection.normal;
+    }
+
+    // Huella del rayo sobre la superficie, más ancha cuanto más de lado la cruza. Los rayos
+    // secundarios salen de aquí con el cono ya ensanchado (los reflejos no lo curvan)
+    let cone_width = bounce.cone.width_at(closest_intersection.distance);
+    let bounce = Bounce { cone: RayCone { width: cone_width, ..bounce.cone }, ..bounce };
+    let cos_incidence = ray_direction.normalize().dot(&closest_intersection.normal).abs().max(MIN_FOOTPRINT_COS);
+    let footprint = cone_width / cos_incidence * closest_intersection.uv_scale;
+
+    // Obtener el color difuso del material
+    let diffuse_color = closest_intersection.material.get_diffuse_color(
+        closest_intersection.u,
+        closest_intersection.v,
+        &closest_intersection.point,
+        footprint,
+    );
+
+    // Acumulamos la luz en punto flotante: las luces sustractivas pueden dejar valores negativos
+    // a mitad de camino, que solo se recortan a [0, 255] al final
+    let diffuse_rgb = [diffuse_color.r, diffuse_color.g, diffuse_color.b].map(|c| c as f32);
+    let mut lighting = [0.0f32; 3];
+    // Lo mismo separado por parte, solo para `ShadingBreakdown`
+    let mut ambient = [0.0f32; 3];
+    let mut diffuse_light = [0.0f32; 3];
+    let mut specular_light = [0.0f32; 3];
+    let mut shadowed = [0.0f32; 3];
+
+    // Iterar sobre cada luz
+    for light in lights {
+        let light_rgb = [light.color.r, light.color.g, light.color.b].map(|c| c as f32);
+        let sign = if light.subtractive { -1.0 } else { 1.0 };
+
+        // Si la luz es ambiental, sumamos su contribución y continuamos (salvo en la vista de solo luz directa)
+        if light.is_ambient() {
+            if config.debug_view == DebugView::DirectLighting || !light.diffuse {
+                continue;
+            }
+            for c in 0..3 {
+                let contribution = sign * diffuse_rgb[c] * (light_rgb[c] / 255.0) * light.intensity;
+                lighting[c] += contribution;
+                if BREAKDOWN {
+                    ambient[c] += contribution;
+                }
+            }
+            continue;
+        }
+
+        // Una luz sin difusa ni especular no aporta nada: ni siquiera lanzamos su rayo de sombra
+        if !light.diffuse && !light.specular {
+            continue;
+        }
+
+        // Calcular la dirección de la luz y la intensidad difusa usando la ley de Lambert
+        let light_dir = (light.position - closest_intersection.point).normalize();
+        let diffuse_intensity = closest_intersection.normal.dot(&light_dir);
+
+        // Una superficie de espaldas a la luz no recibe nada de ella: no hace falta lanzar el rayo de sombra
+        if diffuse_intensity <= 0.0 {
+            continue;
+        }
+
+        // Calcular la intensidad de la sombra
+        let shadow_intensity = if config.shadows { cast_shadow(&closest_intersection, light, objects) } else { 0.0 };
+        let light_intensity = light.intensity * (1.0 - shadow_intensity);
+
+        // Componente especular usando el modelo de Phong
+        let view_dir = (ray_origin - closest_intersection.point).normalize();
+        let reflect_dir = reflect(&-light_dir, &closest_intersection.normal).normalize();
+        let specular_intensity = match &closest_intersection.material.anisotropy {
+            Some(anisotropy) => {
+                let (tangent, bitangent) = orthonormal_basis(&closest_intersection.normal);
+                anisotropy.specular(&closest_intersection.normal, &tangent, &bitangent, &light_dir, &view_dir)
+            }
+            None => specular_power(
+                view_dir.dot(&reflect_dir),
+                closest_intersection.material.specular_exponent(),
+            ),
+        };
+
+        // Sumar (o restar) la componente difusa, teñida por el color de la luz, y la especular; cada
+        // luz puede aportar solo una de las dos
+        let diffuse_weight = if light.diffuse { 1.0 } else { 0.0 };
+        let specular_weight = if light.specular { 1.0 } else { 0.0 };
+        for c in 0..3 {
+            // Aporte por unidad de intensidad de la luz, antes de la sombra
+            let diffuse = diffuse_weight * diffuse_rgb[c] * (light_rgb[c] / 255.0) * closest_intersection.material.albedo[0] * diffuse_intensity;
+            let specular = specular_weight * light_rgb[c] * closest_intersection.material.albedo[1] * specular_intensity;
+            lighting[c] += sign * (diffuse * light_intensity + specular * light_intensity);
+            if BREAKDOWN {
+                diffuse_light[c] += sign * diffuse * light_intensity;
+                specular_light[c] += sign * specular * light_intensity;
+                shadowed[c] += sign * (diffuse + specular) * light.intensity * shadow_intensity;
+            }
+        }
+    }
+
+    let final_color = color::Color::from_f32(lighting[0], lighting[1], lighting[2]);
+
+    // Vista de depuración de solo luz directa: sin reflexión, refracción ni emisión
+    if config.debug_view == DebugView::DirectLighting {
+        return ShadingBreakdown::flat(final_color);
+    }
+
+    let material = &closest_intersection.material;
+    let reflectivity = material.albedo[2];
+    let transparency = material.albedo[3];
+
+    // Las partes de la luz directa, con el peso que tiene la superficie en el color final
+    let surface_weight = 1.0 - reflectivity - transparency;
+    let weighted = |channels: [f32; 3]| {
+        color::Color::from_f32(channels[0] * surface_weight, channels[1] * surface_weight, channels[2] * surface_weight)
+    };
+    let mut breakdown = if BREAKDOWN {
+        ShadingBreakdown {
+            color: final_color,
+            ambient: weighted(ambient),
+            diffuse: weighted(diffuse_light),
+            specular: weighted(specular_light),
+            shadow: weighted(shadowed),
+            ..ShadingBreakdown::default()
+        }
+    } else {
+        ShadingBreakdown::flat(final_color)
+    };
+
+    // Los materiales opacos, sin reflexión ni emisión (la mayoría del terreno) no lanzan rayos secundarios
+    if !material.is_reflective() && !material.is_transparent() && !material.is_emissive() {
+        return breakdown;
+    }
+
+    // Componente de reflexión
+    let mut reflect_color = color::Color::new(0, 0, 0);
+    // Un rayo secundario que aportaría menos que `min_contribution` del píxel no se lanza: así la
+    // recursión se corta antes en caminos tenues y sigue donde importa (entre dos espejos)
+    if material.is_reflective() && bounce.throughput * reflectivity >= config.min_contribution {
+        let reflect_dir = reflect(ray_direction, &closest_intersection.normal).normalize();
+        reflect_color = trace_ray::<false>(&closest_intersection.point, &reflect_dir, objects, lights, config, bounce.next(reflectivity)).color;
+        // El tinte del material colorea el reflejo (por ejemplo, un espejo dorado calienta lo que refleja)
+        let tint = material.reflection_color;
+        // Con película delgada, además, el color del reflejo cambia con el ángulo de vista
+        let film = match &material.thin_film {
+            Some(thin_film) => thin_film.tint(-ray_direction.normalize().dot(&closest_intersection.normal)),
+            None => [1.0; 3],
+        };
+        // El peso `reflectivity` se aplica una sola vez, al combinar abajo
+        reflect_color = color::Color::from_f32(
+            reflect_color.r as f32 * tint.rf() * film[0],
+            reflect_color.g as f32 * tint.gf() * film[1],
+            reflect_color.b as f32 * tint.bf() * film[2],
+        );
+    }
+
+    // Componente de refracción
+    let mut refract_color = color::Color::new(0, 0, 0);
+    if material.is_transparent() && config.transparency == Transparency::DepthPeeling {
+        if !bounce.skip_refraction && bounce.throughput * transparency >= config.min_contribution {
+            refract_color = peel_layers(&closest_intersection.point, ray_direction, objects, lights, config, bounce.next(transparency));
+        }
+    } else if material.is_transparent() && bounce.throughput * transparency >= config.min_contribution {
+        let refract_dir = refract(
+            ray_direction,
+            &closest_intersection.normal,
+            closest_intersection.front_face,
+            config.ambient_ior,
+            material.refractive_index,
+        ).normalize();
+        refract_color = if material.refraction_roughness > 0.0 {
+            rough_refraction(&closest_intersection.point, &refract_dir, material.refraction_roughness, objects, lights, config, bounce.next(transparency))
+        } else {
+            trace_ray::<false>(&closest_intersection.point, &refract_dir, objects, lights, config, bounce.next(transparency)).color
+        };
+    }
+
+    // Componente de emisión, independiente de la iluminación
+    let emission_color = material.get_emission_color(closest_intersection.u, closest_intersection.v);
+
+    // Combinar difusa, especular, reflejada, refractada y emitida. Lo que se ve a través de la superficie
+    // ya viene compuesto por el rayo refractado, así que superficies transparentes apiladas se
+    // componen en orden: cada una deja pasar `transparency` de lo que hay detrás
+    let combine = |surface: u8, reflected: u8, refracted: u8, emitted: u8| {
+        surface as f32 * (1.0 - reflectivity - transparency) + reflected as f32 * reflectivity + refracted as f32 * transparency + emitted as f32
+    };
+    breakdown.color = color::Color::from_f32(
+        combine(final_color.r, reflect_color.r, refract_color.r, emission_color.r),
+        combine(final_color.g, reflect_color.g, refract_color.g, emission_color.g),
+        combine(final_color.b, reflect_color.b, refract_color.b, emission_color.b),
+    );
+    if BREAKDOWN {
+        let scaled = |color: color::Color, weight: f32| {
+            color::Color::from_f32(color.r as f32 * weight, color.g as f32 * weight, color.b as f32 * weight)
+        };
+        breakdown.reflection = scaled(reflect_color, reflectivity);
+        breakdown.refraction = scaled(refract_color, transparency);
+        breakdown.emission = emission_color;
+    }
+    breakdown
+}
+
+
+
+
+
+// Máximo de capas transparentes que junta `peel_layers` detrás de una superficie
+const MAX_PEELED_LAYERS: usize = 16;
+
+// Transparencia por capas (depth peeling): junta en orden de distancia todas las superficies
+// transparentes que cruza el rayo recto (sin desviarse) hasta la primera opaca o el fondo, y las
+// compone de atrás hacia adelante. El orden no depende del orden de los objetos en la escena ni
+// gasta la profundidad de recursión, así que una pila de varias capas de agua se ve completa.
+fn peel_layers(
+    origin: &Vec3,
+    ray_direction: &Vec3,
+    objects: &[Box<dyn RayIntersect>],
+    lights: &[Light],
+    config: &RenderConfig,
+    bounce: Bounce,
+) -> color::Color {
+    // Cada capa se guarda como el origen desde el que es el impacto más cercano y su transparencia
+    let mut layers: Vec<(Vec3, f32)> = Vec::new();
+    let mut start = *origin;
+    while layers.len() < MAX_PEELED_LAYERS {
+        match closest_hit(&start, ray_direction, objects) {
+            Some((_, hit)) if hit.material.is_transparent() => {
+                layers.push((start, hit.material.albedo[3]));
+                start = hit.point;
+            }
+            _ => break,
+        }
+    }
+
+    // Lo que queda detrás de todas las capas (una superficie opaca o el fondo) se sombrea normalmente
+    let behind = trace_ray::<false>(&start, ray_direction, objects, lights, config, bounce).color;
+    let mut color = [behind.r, behind.g, behind.b].map(|c| c as f32);
+    for (layer_origin, transparency) in layers.into_iter().rev() {
+        let layer_bounce = Bounce { skip_refraction: true, ..bounce };
+        let surface = trace_ray::<false>(&layer_origin, ray_direction, objects, lights, config, layer_bounce).color;
+        let surface = [surface.r, surface.g, surface.b];
+        for c in 0..3 {
+            color[c] = surface[c] as f32 + color[c] * transparency;
+        }
+    }
+    color::Color::from_f32(color[0], color[1], color[2])
+}
+
+
+// Refracción de un material rugoso (vidrio esmerilado): promedia varios rayos cuya dirección se
+// desvía de `refract_dir` hacia una dirección aleatoria con peso coseno, más cuanto mayor sea la
+// rugosidad. La semilla sale del punto de impacto, así que el resultado es determinista.
+fn rough_refraction(
+    point: &Vec3,
+    refract_dir: &Vec3,
+    roughness: f32,
+    objects: &[Box<dyn RayIntersect>],
+    lights: &[Light],
+    config: &RenderConfig,
+    bounce: Bounce,
+) -> color::Color {
+    let samples = config.refraction_samples.max(1);
+    let point_seed = hash(point.x.to_bits() ^ hash(point.y.to_bits() ^ hash(point.z.to_bits())));
+    let roughness = roughness.clamp(0.0, 1.0);
+
+    let mut sum = [0.0f32; 3];
+    for sample in 0..samples {
+        let scattered = sample_hemisphere_cosine(refract_dir, hash(point_seed ^ sample));
+        let direction = refract_dir.lerp(&scattered, roughness).normalize();
+        let color = trace_ray::<false>(point, &direction, objects, lights, config, bounce).color;
+        sum[0] += color.r as f32;
+        sum[1] += color.g as f32;
+        sum[2] += color.b as f32;
+    }
+
+    // Redondeado como el promedio de las muestras por píxel: truncar oscurecería el vidrio esmerilado
+    let average = sum.map(|channel| (channel / samples as f32).round().min(255.0) as u8);
+    color::Color::new(average[0], average[1], average[2])
+}
+
+
+// Modos de sombreado para depurar la geometría o aislar partes de la iluminación
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum DebugView {
+    #[default]
+    Off,
+    Normals,         // Colorea cada superficie según su normal
+    Uv,              // Colorea según las coordenadas de textura: rojo = u, verde = v
+    DirectLighting,  // Solo difusa, especular y sombras: sin luz ambiental, reflexión, refracción ni emisión
+}
+
+
+// Cómo se ve lo que hay detrás de una superficie transparente
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Transparency {
+    #[default]
+    Recursive,     // Un rayo refractado (desviado por el índice de refracción) por superficie, hasta el límite de profundidad
+    DepthPeeling,  // Todas las capas del rayo recto, compuestas de atrás hacia adelante; sin desvío ni límite de profundidad
+}
+
+
+// Operador que comprime el rango de cada canal, ya multiplicado por la exposición, a [0, 1]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ToneMap {
+    #[default]
+    Clamp,       // Recorta lo que pase de 1, como sin operador
+    Reinhard,    // x / (1 + x): comprime las luces altas de forma suave
+    AcesFilmic,  // Curva fílmica ACES (aproximación de Narkowicz), con más contraste en los medios tonos
+}
+
+impl ToneMap {
+    // Nombre usado en la línea de comandos: "clamp", "reinhard" o "aces"
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.to_ascii_lowercase().as_str() {
+            "clamp" => Some(ToneMap::Clamp),
+            "reinhard" => Some(ToneMap::Reinhard),
+            "aces" => Some(ToneMap::AcesFilmic),
+            _ => None,
+        }
+    }
+
+    pub fn apply(self, value: f32) -> f32 {
+        let value = value.max(0.0);
+        match self {
+            ToneMap::Clamp => value.min(1.0),
+            ToneMap::Reinhard => value / (1.0 + value),
+            ToneMap::AcesFilmic => {
+                ((value * (2.51 * value + 0.03)) / (value * (2.43 * value + 0.59) + 0.14)).clamp(0.0, 1.0)
+            }
+        }
+    }
+}
+
+
+// Cómo se generan los rayos primarios a partir de la cámara
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum Projection {
+    #[default]
+    Perspective,
+    Orthographic { view_height: f32 },  // Rayos paralelos; view_height es la altura visible en unidades del mundo
+}
+
+
+// Opciones que controlan cómo se renderiza un cuadro
+#[derive(Debug, Clone)]
+pub struct RenderConfig {
+    pub region: Option<(usize, usize, usize, usize)>,  // (x0, y0, x1, y1), con x1 e y1 exclusivos
+    pub exposure: f32,                                 // Multiplicador aplicado al color final
+    pub ambient_ior: f32,                              // Índice de refracción del medio que rodea a los objetos
+    pub background: Background,                        // Fondo para los rayos de cámara que no chocan con nada
+    pub environment: Option<Background>,               // Entorno que ven los reflejos y refracciones; None usa `background`
+    pub chunk_size: usize,                             // Filas por bloque procesado en paralelo
+    pub saturation: f32,                               // 1 deja el color intacto, 0 lo convierte a escala de grises
+    pub brightness: f32,                               // Desplazamiento aditivo en [-1, 1]
+    pub contrast: f32,                                 // Escala alrededor del gris medio; 1 no cambia nada
+    pub samples_per_pixel: u32,                        // Muestras con desplazamiento aleatorio por píxel; 1 desactiva el supermuestreo
+    pub firefly_clamp: Option<f32>,                    // Luminancia máxima de cada muestra en [0, 1]; None para un render sin sesgo
+    pub debug_view: DebugView,                         // Sombreado de depuración; Off para el render normal
+    pub time_budget: Option<Duration>,                 // Tiempo máximo por cuadro; None renderiza siempre completo
+    pub alpha_matte: bool,                             // Guarda en el byte alto la cobertura (ARGB): 0 donde solo se ve el fondo
+    pub projection: Projection,                        // Perspectiva (por defecto) u ortográfica
+    pub edge_samples: u32,                             // Muestras para los píxeles en el borde entre objetos; sin efecto si no supera samples_per_pixel
+    pub denoise_iterations: u32,                       // Intensidad del filtro à-trous sobre el cuadro final; 0 lo desactiva. No se aplica a cuadros parciales (región o tiempo agotado)
+    pub tone_map: ToneMap,                             // Operador de tono aplicado tras la exposición; Clamp solo recorta
+    pub shadows: bool,                                 // false no lanza rayos de sombra: todo queda iluminado (más rápido)
+    pub refraction_samples: u32,                       // Rayos promediados en la refracción de materiales esmerilados
+    pub transparency: Transparency,                    // Cómo se compone lo que se ve a través de superficies transparentes
+    pub min_contribution: f32,                         // Aporte mínimo al píxel para lanzar un rayo reflejado o refractado; 0 los lanza siempre
+    pub thread_pool: Option<Arc<ThreadPool>>,          // Hilos que usa el render; None usa el pool global con todos los núcleos
+    pub dither: f32,                                   // Amplitud del tramado antes de cuantizar a 8 bits, en valores de código; 0 lo desactiva
+    pub max_depth: u32,                                // Rayos encadenados como máximo (el de cámara y sus reflejos o refracciones); los siguientes ven el fondo
+}
+
+impl Default for RenderConfig {
+    fn default() -> Self {
+        RenderConfig {
+            region: None,
+            exposure: 1.0,
+            ambient_ior: 1.0,
+            background: Background::Solid(color::Color::new(4, 12, 36)),
+            environment: None,
+            chunk_size: 8,
+            saturation: 1.0,
+            brightness: 0.0,
+            contrast: 1.0,
+            samples_per_pixel: 1,
+            firefly_clamp: None,
+            debug_view: DebugView::Off,
+            time_budget: None,
+            alpha_matte: false,
+            projection: Projection::Perspective,
+            edge_samples: 0,
+            denoise_iterations: 0,
+            tone_map: ToneMap::Clamp,
+            shadows: true,
+            refraction_samples: 4,
+            transparency: Transparency::Recursive,
+            min_contribution: 0.01,
+            thread_pool: None,
+            dither: 0.5,
+            max_depth: 2,
+        }
+    }
+}
+
+// Crea un pool de `threads` hilos para `RenderConfig::thread_pool`, por ejemplo para renderizar en
+// segundo plano sin ocupar toda la máquina o para medir tiempos comparables entre equipos
+pub fn thread_pool(threads: usize) -> Result<Arc<ThreadPool>, rayon::ThreadPoolBuildError> {
+    rayon::ThreadPoolBuilder::new().num_threads(threads).build().map(Arc::new)
+}
+
+// Ejecuta `work` dentro del pool de la configuración, si hay uno; así el trabajo paralelo que haga
+// (`par_iter`, `par_chunks_mut`) queda limitado a esos hilos
+pub(crate) fn in_thread_pool<R: Send>(config: &RenderConfig, work: impl FnOnce() -> R + Send) -> R {
+    match &config.thread_pool {
+        Some(pool) => pool.install(work),
+        None => work(),
+    }
+}
+
+// Tamaño de bloque que reparte unos 4 bloques por hilo, para balancear la carga sin
+// fragmentar demasiado el trabajo; en imágenes pequeñas baja hasta una fila por bloque. Los hilos
+// son los del pool de `config`, o los del pool global si no tiene uno.
+pub fn auto_chunk_size(height: usize, config: &RenderConfig) -> usize {
+    let target_chunks = in_thread_pool(config, rayon::current_num_threads) * 4;
+    (height / target_chunks).clamp(1, 32)
+}
+
+
+// Estadísticas de un cuadro renderizado
+#[derive(Debug, Clone, Copy, Default)]
+pub struct RenderStats {
+    pub pixels: u64,        // Píxeles calculados (rayos primarios)
+    pub rays: u64,          // Rayos totales: primarios, de sombra, reflejados y refractados
+    pub elapsed: Duration,  // Tiempo que tomó el cuadro
+    pub complete: bool,     // false si se agotó el presupuesto de tiempo antes de terminar
+}
+
+impl RenderStats {
+    pub fn rays_per_second(&self) -> f64 {
+        let seconds = self.elapsed.as_secs_f64();
+        if seconds > 0.0 { self.rays as f64 / seconds } else { 0.0 }
+    }
+}
+
+
+// Matriz de Bayer de 4x4: umbrales repartidos de forma que cualquier bloque de 4x4 píxeles los use todos
+const BAYER_4X4: [[u8; 4]; 4] = [[0, 8, 2, 10], [12, 4, 14, 6], [3, 11, 1, 9], [15, 7, 13, 5]];
+
+// Desplazamiento del tramado ordenado en el píxel (x, y), en [-0.5, 0.5)
+fn ordered_dither(x: usize, y: usize) -> f32 {
+    (BAYER_4X4[y % 4][x % 4] as f32 + 0.5) / 16.0 - 0.5
+}
+
+// Saturación, exposición con su operador de tono, contraste y brillo aplicados al color final antes de
+// empaquetarlo como 0RGB. `pixel` es la posición (x, y) en el framebuffer, que elige el umbral del
+// tramado: sumar menos de un valor de código antes de cuantizar convierte los escalones de un
+// degradado en una mezcla fina de los dos valores vecinos.
+pub(crate) fn post_process(color: color::Color, config: &RenderConfig, pixel: (usize, usize)) -> u32 {
+    let luminance = color.luminance();
+    let dither = 0.5 + ordered_dither(pixel.0, pixel.1) * 2.0 * config.dither;
+    let channels = [color.r, color.g, color.b].map(|channel| {
+        let value = channel as f32 / 255.0;
+        let value = luminance + (value - luminance) * config.saturation;
+        let value = config.tone_map.apply(value * config.exposure);
+        let value = (value - 0.5) * config.contrast + 0.5 + config.brightness;
+        // Una exposición o un contraste no finitos dan NaN o infinito: el canal queda negro
+        if value.is_finite() { (value.clamp(0.0, 1.0) * 255.0 + dither).min(255.0) as u32 } else { 0 }
+    });
+
+    (channels[0] << 16) | (channels[1] << 8) | channels[2]
+}
+
+
+// Origen y dirección del rayo primario que pasa por la posición (x, y) del framebuffer, en píxeles
+pub(crate) fn primary_ray(camera: &Camera, projection: Projection, x: f32, y: f32, width: usize, height: usize) -> (Vec3, Vec3) {
+    let screen_x = (2.0 * x) / width as f32 - 1.0;
+    let screen_x = screen_x * (width as f32 / height as f32);
+    let screen_y = -((2.0 * y) / height as f32 - 1.0);
+
+    match projection {
+        // El fov es vertical: la altura visible fija la escala y el ancho crece con la relación de aspecto,
+        // así los píxeles siguen siendo cuadrados en cualquier ventana
+        Projection::Perspective => {
+            let half_height = camera.half_height();
+            let ray_direction = nalgebra_glm::normalize(&Vec3::new(screen_x * half_height, screen_y * half_height, -1.0));
+            (camera.eye, camera.basis_change(&ray_direction))
+        }
+        // Todos los rayos son paralelos a la dirección de vista y salen de un plano centrado en el ojo
+        Projection::Orthographic { view_height } => {
+            let forward = (camera.center - camera.eye).normalize();
+            let right = forward.cross(&camera.up).normalize();
+            let up = right.cross(&forward).normalize();
+            let offset = (right * screen_x + up * screen_y) * view_height * 0.5;
+            (camera.eye + offset, forward)
+        }
+    }
+}
+
+
+// Índice en `objects` e intersección del objeto más cercano que ve el rayo, o None si solo ve el fondo.
+// Una `Bvh` cuenta como sus hijos, cada uno con su índice, igual que en `bvh::flatten`.
+fn closest_hit(ray_origin: &Vec3, ray_direction: &Vec3, objects: &[Box<dyn RayIntersect>]) -> Option<(usize, Intersect)> {
+    let mut closest = None;
+    let mut closest_distance = f32::INFINITY;
+    let mut first_id = 0;
+    for object in objects {
+        let (hit, count) = match object.as_any().downcast_ref::<Bvh>() {
+            Some(bvh) => (bvh.closest_hit(ray_origin, ray_direction, RAY_EPSILON, closest_distance), bvh.len()),
+            None => {
+                let intersection = object.ray_intersect(ray_origin, ray_direction, RAY_EPSILON, closest_distance);
+                (intersection.is_intersecting.then_some((0, intersection)), 1)
+            }
+        };
+        if let Some((child, intersection)) = hit {
+            closest_distance = intersection.distance;
+            closest = Some((first_id + child, intersection));
+        }
+        first_id += count;
+    }
+    closest
+}
+
+// Índice en `objects` del objeto más cercano que ve el rayo primario (su id), o None si solo ve el fondo
+pub fn primary_object_id(ray_origin: &Vec3, ray_direction: &Vec3, objects: &[Box<dyn RayIntersect>]) -> Option<usize> {
+    closest_hit(ray_origin, ray_direction, objects).map(|(id, _)| id)
+}
+
+
+// Lo que se ve en un píxel de la pantalla: el objeto, el punto del mundo y la normal de la superficie
+#[derive(Debug, Clone, Copy)]
+pub struct Pick {
+    pub object: usize,
+    pub point: Vec3,
+    pub normal: Vec3,
+}
+
+// Lanza el rayo primario por la posición (x, y) del framebuffer, en píxeles, y devuelve lo que encuentra
+pub fn pick(
+    objects: &[Box<dyn RayIntersect>],
+    camera: &Camera,
+    config: &RenderConfig,
+    x: f32,
+    y: f32,
+    width: usize,
+    height: usize,
+) -> Option<Pick> {
+    let (origin, direction) = primary_ray(camera, config.projection, x, y, width, height);
+    closest_hit(&origin, &direction, objects).map(|(object, intersection)| Pick {
+        object,
+        point: intersection.point,
+        normal: intersection.normal,
+    })
+}
+
+
+// Factor que limita la luminancia de una muestra para suprimir "luciérnagas" (píxeles muy
+// brillantes por muestras raras), conservando su tono
+pub(crate) fn firefly_scale(color: color::Color, max_luminance: Option<f32>) -> f32 {
+    match max_luminance {
+        Some(max) if color.luminance() > max => max.max(0.0) / color.luminance(),
+        _ => 1.0,
+    }
+}
+
+
+pub fn render(
+    framebuffer: &mut [u32], 
+    width: usize, 
+    height: usize, 
+    objects: &[Box<dyn RayIntersect>], 
+    camera: &Camera, 
+    lights: &[Light],
+    config: &RenderConfig,
+) -> RenderStats {
+    in_thread_pool(config, || render_frame(framebuffer, width, height, objects, camera, lights, config))
+}
+
+fn render_frame(
+    framebuffer: &mut [u32],
+    width: usize,
+    height: usize,
+    objects: &[Box<dyn RayIntersect>],
+    camera: &Camera,
+    lights: &[Light],
+    config: &RenderConfig,
+) -> RenderStats {
+    let start = Instant::now();
+    let total_rays = AtomicU64::new(0);
+    let total_pixels = AtomicU64::new(0);
+
+    // Con un presupuesto de tiempo, al vencer el plazo dejamos de empezar bloques nuevos; los que
+    // faltan conservan el contenido del cuadro anterior
+    let deadline = config.time_budget.map(|budget| start + budget);
+    let out_of_time = AtomicBool::new(false);
+
+    // Si no hay región, se renderiza el framebuffer completo
+    let (x0, y0, x1, y1) = match config.region {
+        Some((x0, y0, x1, y1)) => (x0.min(width), y0.min(height), x1.min(width), y1.min(height)),
+        None => (0, 0, width, height),
+    };
+
+    let chunk_size = config.chunk_size.max(1);  // Tamaño de bloque para procesar en paralelo
+    let samples = config.samples_per_pixel.max(1);
+    // Los rayos de cámara llevan el cono de su píxel, para filtrar las texturas lejanas
+    let primary = Bounce { cone: RayCone::pixel(camera, config.projection, height), ..Bounce::primary(0) };
+
+    // Para el antialiasing por bordes guardamos el id del objeto que ve cada píxel; solo se
+    // supermuestrean los píxeles cuyo id difiere del de algún vecino (siluetas reales, no texturas).
+    // Basta con la región y un píxel alrededor, que son los vecinos de sus bordes.
+    let object_ids: Option<Vec<Option<usize>>> = (config.edge_samples > samples).then(|| {
+        (0..width * height)
+            .into_par_iter()
+            .map(|i| {
+                let (x, y) = (i % width, i / width);
+                if x + 1 < x0 || x > x1 || y + 1 < y0 || y > y1 {
+                    return None;
+                }
+                let (ray_origin, ray_direction) = primary_ray(camera, config.projection, x as f32, y as f32, width, height);
+                primary_object_id(&ray_origin, &ray_direction, objects)
+            })
+            .collect()
+    });
+    let is_edge = |x: usize, y: usize| match &object_ids {
+        Some(ids) => {
+            let id = ids[y * width + x];
+            (x > 0 && ids[y * width + x - 1] != id)
+                || (x + 1 < width && ids[y * width + x + 1] != id)
+                || (y > 0 && ids[(y - 1) * width + x] != id)
+                || (y + 1 < height && ids[(y + 1) * width + x] != id)
+        }
+        None => false,
+    };
+
+    framebuffer.par_chunks_mut(width * chunk_size).enumerate().for_each(|(chunk_idx, chunk)| {
+        let base_y = chunk_idx * chunk_size;
+        if out_of_time.load(Ordering::Relaxed) {
+            return;
+        }
+        if deadline.is_some_and(|deadline| Instant::now() >= deadline) {
+            out_of_time.store(true, Ordering::Relaxed);
+            return;
+        }
+
+        let rays_before = rays_cast();
+        let rows = chunk.len() / width;
+        let rows_in_region = (base_y..base_y + rows).filter(|y| *y >= y0 && *y < y1).count();
+    
+        for (y, row) in chunk.chunks_mut(width).enumerate() {
+            // Las filas fuera de la región se dejan intactas
+            if base_y + y < y0 || base_y + y >= y1 {
+                continue;
+            }
+
+            row[x0..x1.max(x0)].iter_mut().enumerate().for_each(|(x, pixel)| {
+                let x = x0 + x;
+                let samples = if is_edge(x, base_y + y) { config.edge_samples } else { samples };
+
+                // Con una sola muestra se traza un solo rayo, en la esquina del píxel
+                if samples == 1 {
+                    let (ray_origin, ray_direction) = primary_ray(camera, config.projection, x as f32, (base_y + y) as f32, width, height);
+                    let pixel_color = trace_ray::<false>(&ray_origin, &ray_direction, objects, lights, config, primary).color;
+                    *pixel = post_process(pixel_color, config, (x, base_y + y));
+                    if config.alpha_matte && primary_object_id(&ray_origin, &ray_direction, objects).is_some() {
+                        *pixel |= 0xFF00_0000;
+                    }
+                    return;
+                }
+
+                // Promediamos en punto flotante las muestras desplazadas dentro del píxel
+                let mut sum = [0.0f32; 3];
+                let mut hits = 0;
+                for sample in 0..samples {
+                    let (jitter_x, jitter_y) = pixel_jitter(x, base_y + y, sample);
+                    let (ray_origin, ray_direction) = primary_ray(
+                        camera,
+                        config.projection,
+                        x as f32 + jitter_x,
+                        (base_y + y) as f32 + jitter_y,
+                        width,
+                        height,
+                    );
+                    let sample_color = trace_ray::<false>(&ray_origin, &ray_direction, objects, lights, config, primary).color;
+                    let scale = firefly_scale(sample_color, config.firefly_clamp);
+                    sum[0] += sample_color.r as f32 * scale;
+                    sum[1] += sample_color.g as f32 * scale;
+                    sum[2] += sample_color.b as f32 * scale;
+                    if config.alpha_matte && primary_object_id(&ray_origin, &ray_direction, objects).is_some() {
+                        hits += 1;
+                    }
+                }
+
+                let average = sum.map(|channel| (channel / samples as f32).round().min(255.0) as u8);
+                *pixel = post_process(color::Color::new(average[0], average[1], average[2]), config, (x, base_y + y));
+                // La cobertura parcial en los bordes deja un canal alfa suavizado
+                let alpha = (hits as f32 / samples as f32 * 255.0).round() as u32;
+                *pixel |= alpha << 24;
+            });
+        }
+
+        total_rays.fetch_add(rays_cast() - rays_before, Ordering::Relaxed);
+        total_pixels.fetch_add((rows_in_region * x1.saturating_sub(x0)) as u64, Ordering::Relaxed);
+    });
+
+    // Eliminación de ruido guiada por normales y profundidad, para renders con pocas muestras. Solo en
+    // cuadros completos: el filtro mezcla cada píxel con vecinos lejanos, que en un cuadro parcial
+    // (una región, o bloques que se quedaron sin tiempo) son restos del cuadro anterior, y su G-buffer
+    // recorrería toda la imagen.
+    let complete = !out_of_time.into_inner();
+    if config.denoise_iterations > 0 && complete && (x0, y0, x1, y1) == (0, 0, width, height) {
+        let gbuffer = GBuffer::render(width, height, objects, camera, config);
+        denoise_atrous(framebuffer, &gbuffer, config);
+    }
+
+    RenderStats {
+        pixels: total_pixels.into_inner(),
+        rays: total_rays.into_inner(),
+        elapsed: start.elapsed(),
+        complete,
+    }
+}
+
+
+// Resultado de muestrear un solo píxel muchas veces
+#[derive(Debug, Clone, Copy)]
+pub struct PixelEstimate {
+    pub color: color::Color,      // Promedio de las muestras, antes del postproceso
+    pub luminance_variance: f32,  // Varianza de la luminancia de las muestras, en [0, 1]
+}
+
+// Muestrea el píxel (x, y) de una imagen de `size` (ancho, alto) con `samples` rayos repartidos
+// entre los hilos. Sirve para depurar el color de un píxel ruidoso y comprobar que converge.
+pub fn cast_pixel_hq(
+    scene: &Scene,
+    config: &RenderConfig,
+    size: (usize, usize),
+    pixel: (usize, usize),
+    samples: u32,
+) -> PixelEstimate {
+    let (width, height) = size;
+    let (x, y) = pixel;
+    let samples = samples.max(1);
+
+    // Cada muestra aporta su color (ya limitado contra luciérnagas) y su luminancia
+    let (sum, luminance_sum, luminance_sq_sum) = (0..samples)
+        .into_par_iter()
+        .map(|sample| {
+            let (jitter_x, jitter_y) = pixel_jitter(x, y, sample);
+            let (ray_origin, ray_direction) =
+                primary_ray(&scene.camera, config.projection, x as f32 + jitter_x, y as f32 + jitter_y, width, height);
+            let sample_color =
+                trace_camera_ray::<false>(&ray_origin, &ray_direction, &scene.objects, &scene.lights, &scene.camera, config, height)
+                    .color;
+            let scale = firefly_scale(sample_color, config.firefly_clamp);
+            let luminance = sample_color.luminance() * scale;
+            (
+                [sample_color.r as f32 * scale, sample_color.g as f32 * scale, sample_color.b as f32 * scale],
+                luminance,
+                luminance * luminance,
+            )
+        })
+        .reduce(
+            || ([0.0; 3], 0.0, 0.0),
+            |a, b| ([a.0[0] + b.0[0], a.0[1] + b.0[1], a.0[2] + b.0[2]], a.1 + b.1, a.2 + b.2),
+        );
+
+    let n = samples as f32;
+    let average = sum.map(|channel| (channel / n).round().min(255.0) as u8);
+    let mean_luminance = luminance_sum / n;
+
+    PixelEstimate {
+        color: color::Color::new(average[0], average[1], average[2]),
+        luminance_variance: (luminance_sq_sum / n - mean_luminance * mean_luminance).max(0.0),
+    }
+}
+
+
+// Renderiza un par estereoscópico lado a lado: el ojo izquierdo en la mitad izquierda del
+// framebuffer y el derecho en la derecha. Cada ojo se separa interocular / 2 del centro a lo
+// largo del vector "derecha" de la cámara, con ejes de vista paralelos; con interocular 0
+// ambas mitades son la misma imagen.
+pub fn render_stereo(
+    framebuffer: &mut [u32],
+    width: usize,
+    height: usize,
+    scene: &Scene,
+    config: &RenderConfig,
+    interocular: f32,
+) -> RenderStats {
+    let half_width = width / 2;
+    let forward = (scene.camera.center - scene.camera.eye).normalize();
+    let right = forward.cross(&scene.camera.up).normalize();
+
+    let mut stats = RenderStats { complete: true, ..RenderStats::default() };
+    let mut eye_buffer = vec![0; half_width * height];
+
+    for (side, offset) in [(0, -0.5), (1, 0.5)] {
+        let shift = right * interocular * offset;
+        let eye_camera = Camera {
+            eye: scene.camera.eye + shift,
+            center: scene.camera.center + shift,
+            ..scene.camera.clone()
+        };
+
+        let eye_stats = render(&mut eye_buffer, half_width, height, &scene.objects, &eye_camera, &scene.lights, config);
+        stats.pixels += eye_stats.pixels;
+        stats.rays += eye_stats.rays;
+        stats.elapsed += eye_stats.elapsed;
+        stats.complete &= eye_stats.complete;
+
+        for (row, eye_row) in framebuffer.chunks_mut(width).zip(eye_buffer.chunks(half_width)) {
+            row[side * half_width..(side + 1) * half_width].copy_from_slice(eye_row);
+        }
+    }
+
+    stats
+}
@@ -0,0 +1,63 @@
+// Números pseudoaleatorios deterministas para el muestreo: el mismo píxel y la misma muestra
+// siempre producen el mismo valor, así que los cuadros son reproducibles entre ejecuciones.
+
+use std::f32::consts::PI;
+
+use nalgebra_glm::Vec3;
+
+// Mezcla de bits tipo "lowbias32": pequeños cambios en la entrada cambian toda la salida
+pub fn hash(mut x: u32) -> u32 {
+    x ^= x >> 16;
+    x = x.wrapping_mul(0x7feb_352d);
+    x ^= x >> 15;
+    x = x.wrapping_mul(0x846c_a68b);
+    x ^= x >> 16;
+    x
+}
+
+// Valor en [0, 1) derivado de una semilla
+pub fn random_f32(seed: u32) -> f32 {
+    (hash(seed) >> 8) as f32 / (1u32 << 24) as f32
+}
+
+// Desplazamiento dentro del píxel (x, y), en [0, 1) x [0, 1), para la muestra indicada. La muestra 0
+// cae en la esquina del píxel, el mismo rayo que `render` lanza con una sola muestra, así el primer
+// cuadro acumulado coincide con la vista previa.
+pub fn pixel_jitter(x: usize, y: usize, sample: u32) -> (f32, f32) {
+    if sample == 0 {
+        return (0.0, 0.0);
+    }
+    let seed = hash(x as u32 ^ hash(y as u32 ^ hash(sample)));
+    (random_f32(seed), random_f32(seed ^ 0x9e37_79b9))
+}
+
+// Tangente y bitangente que forman una base ortonormal con la normal dada. La tangente es horizontal
+// (perpendicular al eje Y del mundo), que coincide con la dirección de u en las caras laterales del
+// cubo y en la esfera; en superficies horizontales se usa el eje X, como la u de las caras de arriba
+// y abajo.
+pub fn orthonormal_basis(normal: &Vec3) -> (Vec3, Vec3) {
+    let world_up = Vec3::new(0.0, 1.0, 0.0);
+    let tangent = if normal.cross(&world_up).magnitude() < 1e-4 {
+        Vec3::new(1.0, 0.0, 0.0)
+    } else {
+        world_up.cross(normal).normalize()
+    };
+    (tangent, normal.cross(&tangent).normalize())
+}
+
+// Dirección aleatoria en el hemisferio alrededor de `normal`, con densidad proporcional al coseno
+// con la normal (las direcciones rasantes salen menos). Se muestrea un disco uniforme y se proyecta
+// hacia arriba sobre el hemisferio (método de Malley).
+pub fn sample_hemisphere_cosine(normal: &Vec3, seed: u32) -> Vec3 {
+    let r1 = random_f32(seed);
+    let r2 = random_f32(seed ^ 0x9e37_79b9);
+
+    let radius = r1.sqrt();
+    let angle = 2.0 * PI * r2;
+    let x = radius * angle.cos();
+    let y = radius * angle.sin();
+    let z = (1.0 - r1).max(0.0).sqrt();
+
+    let (tangent, bitangent) = orthonormal_basis(normal);
+    (tangent * x + bitangent * y + normal * z).normalize()
+}
@@ -0,0 +1,280 @@
+use std::fmt;
+
+use crate::background::Background;
+use crate::bvh::flatten;
+use crate::camera::Camera;
+use crate::color::Color;
+use crate::cube::Cube;
+use crate::intersect::RayIntersect;
+use crate::light::Light;
+use crate::material::{Material, MaterialLibrary};
+use crate::plane::Plane;
+use crate::scene_file::{CameraFile, LightFile, MaterialRef, MaterialWriter, ObjectFile, SceneFile};
+use crate::sphere::Sphere;
+use nalgebra_glm::Vec3;
+
+pub struct Scene {
+    pub objects: Vec<Box<dyn RayIntersect>>,
+    pub lights: Vec<Light>,
+    pub camera: Camera,
+    pub background: Background,
+}
+
+impl Scene {
+    pub fn new(objects: Vec<Box<dyn RayIntersect>>, lights: Vec<Light>, camera: Camera) -> Self {
+        Scene {
+            objects,
+            lights,
+            camera,
+            background: Background::Solid(Color::new(4, 12, 36)),
+        }
+    }
+
+    // Reemplaza las luces y el fondo por un día soleado: un sol lejano, un cielo degradado y una
+    // luz ambiental teñida con el color promedio de ese cielo. Los ángulos van en grados.
+    pub fn with_daylight(mut self, azimuth: f32, elevation: f32) -> Self {
+        let sky = Background::daylight_sky(elevation);
+        let sky_color = match &sky {
+            Background::Gradient { horizon, zenith } => horizon.lerp(zenith, 0.5),
+            _ => Color::new(255, 255, 255),
+        };
+
+        self.lights = vec![
+            Light::new(Vec3::zeros(), sky_color, 0.25),
+            Light::directional_sun(azimuth, elevation),
+        ];
+        self.background = sky;
+        self
+    }
+
+    // Caja (min, max) que encierra las cajas delimitadoras de todos los objetos finitos (los planos
+    // infinitos se ignoran); si no hay ninguno devuelve un cubo unitario centrado en el origen
+    pub fn bounds(&self) -> (Vec3, Vec3) {
+        let boxes: Vec<_> = flatten(&self.objects).iter().map(|object| object.bounding_box()).filter(|aabb| aabb.is_finite()).collect();
+        if boxes.is_empty() {
+            return (Vec3::repeat(-0.5), Vec3::repeat(0.5));
+        }
+
+        boxes.iter().fold(
+            (Vec3::repeat(f32::INFINITY), Vec3::repeat(f32::NEG_INFINITY)),
+            |(min, max), aabb| (min.inf(&aabb.min), max.sup(&aabb.max)),
+        )
+    }
+
+    // Guarda la cámara, las luces y los objetos como JSON (ver `scene_file`), con los cambios hechos en
+    // el visor. Devuelve una advertencia por cada objeto que se omitió por ser de un tipo que el formato
+    // no describe y por cada uno cuyos materiales perdieron capas (texturas, ruido...) o retoques al
+    // guardarse. Los nombres se comparan con los de `MaterialLibrary::with_presets`.
+    pub fn save_json(&self, path: &str) -> Result<Vec<String>, String> {
+        self.save_json_with(path, &MaterialLibrary::with_presets())
+    }
+
+    // Igual que `save_json`, pero los materiales con nombre se comparan con los de `library` para
+    // distinguir las copias retocadas (ver `MaterialWriter`)
+    pub fn save_json_with(&self, path: &str, library: &MaterialLibrary) -> Result<Vec<String>, String> {
+        let mut warnings = Vec::new();
+        let mut materials = MaterialWriter::new(library);
+        let mut objects = Vec::new();
+        // Los objetos de una `Bvh` se guardan uno por uno; al cargar vuelven como una lista plana
+        for (i, object) in flatten(&self.objects).into_iter().enumerate() {
+            match ObjectFile::from_object(object, &mut materials) {
+                Some(file) => objects.push(file),
+                None => warnings.push(format!("object {}: type not supported by the scene format, skipped", i)),
+            }
+            let lost = std::mem::take(&mut materials.lost_fields);
+            if !lost.is_empty() {
+                warnings.push(format!("object {}: {} material fields not saved ({})", i, lost.len(), lost.join(", ")));
+            }
+            for name in std::mem::take(&mut materials.lost_tweaks) {
+                warnings.push(format!("object {}: changes to textured material \"{}\" not saved", i, name));
+            }
+        }
+        let file = SceneFile {
+            camera: CameraFile::from_camera(&self.camera),
+            lights: self.lights.iter().map(LightFile::from_light).collect(),
+            objects,
+            materials: materials.table,
+        };
+
+        let json = serde_json::to_string_pretty(&file).map_err(|e| format!("{}: {}", path, e))?;
+        std::fs::write(path, json).map_err(|e| format!("{}: {}", path, e))?;
+        Ok(warnings)
+    }
+
+    // Revisa la escena en busca de errores comunes al armarla y devuelve una advertencia por cada uno
+    pub fn validate(&self) -> Vec<String> {
+        let mut warnings = Vec::new();
+
+        if self.camera.eye == self.camera.center {
+            warnings.push("camera: eye and center are the same point".to_string());
+        }
+        if !self.camera.eye.iter().chain(self.camera.center.iter()).all(|c| c.is_finite()) {
+            warnings.push("camera: eye or center is not finite".to_string());
+        }
+
+        for (i, light) in self.lights.iter().enumerate() {
+            if !light.position.iter().all(|c| c.is_finite()) {
+                warnings.push(format!("light {}: position is not finite", i));
+            }
+            if light.intensity < 0.0 || !light.intensity.is_finite() {
+                warnings.push(format!("light {}: invalid intensity {}", i, light.intensity));
+            }
+        }
+
+        // Los índices son los de `bvh::flatten`, los mismos que muestra el modo de colocación
+        let objects = flatten(&self.objects);
+        let cubes: Vec<(usize, &Cube)> = objects
+            .iter()
+            .enumerate()
+            .filter_map(|(i, object)| object.as_any().downcast_ref::<Cube>().map(|cube| (i, cube)))
+            .collect();
+
+        // Dos cubos en la misma posición se superponen y uno de ellos nunca se ve
+        for (a, (i, cube)) in cubes.iter().enumerate() {
+            for (j, other) in &cubes[a + 1..] {
+                if cube.center() == other.center() {
+                    warnings.push(format!("object {}: same center as object {}", j, i));
+                }
+            }
+        }
+
+        for (i, object) in objects.iter().enumerate() {
+            if let Some(cube) = object.as_any().downcast_ref::<Cube>() {
+                if !cube.center().iter().all(|c| c.is_finite()) {
+                    warnings.push(format!("object {}: cube center is not finite", i));
+                }
+                if cube.size() <= 0.0 || !cube.size().is_finite() {
+                    warnings.push(format!("object {}: degenerate cube size {}", i, cube.size()));
+                }
+                for (face, material) in cube.materials.iter().enumerate() {
+                    validate_material(material, &format!("object {} face {}", i, face), &mut warnings);
+                }
+            }
+            if let Some(sphere) = object.as_any().downcast_ref::<Sphere>() {
+                if !sphere.center.iter().all(|c| c.is_finite()) {
+                    warnings.push(format!("object {}: sphere center is not finite", i));
+                }
+                if sphere.radius <= 0.0 || !sphere.radius.is_finite() {
+                    warnings.push(format!("object {}: degenerate sphere radius {}", i, sphere.radius));
+                }
+                validate_material(&sphere.material, &format!("object {}", i), &mut warnings);
+            }
+            if let Some(plane) = object.as_any().downcast_ref::<Plane>() {
+                if !plane.point.iter().chain(plane.normal.iter()).all(|c| c.is_finite()) {
+                    warnings.push(format!("object {}: plane point or normal is not finite", i));
+                }
+                validate_material(&plane.material, &format!("object {}", i), &mut warnings);
+            }
+        }
+
+        warnings
+    }
+}
+
+// Error al cargar una escena desde JSON; todos indican el archivo y, si se puede, dónde está el problema
+#[derive(Debug)]
+pub enum SceneError {
+    Io { path: String, error: std::io::Error },
+    // JSON mal formado o con un campo del tipo equivocado, en la línea y columna dadas
+    Parse { path: String, line: usize, column: usize, message: String },
+    // `key` es el campo que nombra al material, por ejemplo "objects[3].material"
+    UnknownMaterial { path: String, key: String, name: String },
+    InvalidObject { path: String, key: String, message: String },
+}
+
+impl fmt::Display for SceneError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            SceneError::Io { path, error } => write!(f, "{}: {}", path, error),
+            SceneError::Parse { path, line, column, message } => write!(f, "{}:{}:{}: {}", path, line, column, message),
+            SceneError::UnknownMaterial { path, key, name } => write!(f, "{}: {}: unknown material \"{}\"", path, key, name),
+            SceneError::InvalidObject { path, key, message } => write!(f, "{}: {}: {}", path, key, message),
+        }
+    }
+}
+
+impl std::error::Error for SceneError {}
+
+// Carga una escena guardada con `Scene::save_json` o escrita a mano. Los materiales pueden darse
+// completos o por nombre: primero se buscan en la tabla `materials` del archivo y después entre los
+// de `MaterialLibrary::with_presets`.
+pub fn load_from_json(path: &str) -> Result<Scene, SceneError> {
+    load_from_json_with(path, &MaterialLibrary::with_presets())
+}
+
+// Igual que `load_from_json`, pero los nombres se buscan en `library` (por ejemplo, con materiales
+// texturizados que el formato no puede describir)
+pub fn load_from_json_with(path: &str, library: &MaterialLibrary) -> Result<Scene, SceneError> {
+    let text = std::fs::read_to_string(path).map_err(|error| SceneError::Io { path: path.to_string(), error })?;
+    let file: SceneFile = serde_json::from_str(&text).map_err(|e| SceneError::Parse {
+        path: path.to_string(),
+        line: e.line(),
+        column: e.column(),
+        message: e.to_string(),
+    })?;
+
+    let resolve = |material: &MaterialRef, key: String| -> Result<Material, SceneError> {
+        match material {
+            MaterialRef::Inline(material) => Ok(material.to_material()),
+            // Conserva el nombre, para que al volver a guardar la escena siga siendo una referencia
+            MaterialRef::Name(name) => file
+                .materials
+                .get(name)
+                .map(|material| Material { name: Some(name.as_str().into()), ..material.to_material() })
+                .or_else(|| library.instance(name))
+                .ok_or_else(|| SceneError::UnknownMaterial { path: path.to_string(), key, name: name.clone() }),
+        }
+    };
+
+    let vector = |[x, y, z]: [f32; 3]| Vec3::new(x, y, z);
+    let mut objects: Vec<Box<dyn RayIntersect>> = Vec::new();
+    for (i, object) in file.objects.iter().enumerate() {
+        match object {
+            ObjectFile::Cube { center, size, is_water, casts_shadow, materials } => {
+                if materials.len() != 1 && materials.len() != 6 {
+                    return Err(SceneError::InvalidObject {
+                        path: path.to_string(),
+                        key: format!("objects[{}].materials", i),
+                        message: format!("expected 1 or 6 materials, found {}", materials.len()),
+                    });
+                }
+                let resolved = materials
+                    .iter()
+                    .enumerate()
+                    .map(|(face, material)| resolve(material, format!("objects[{}].materials[{}]", i, face)))
+                    .collect::<Result<Vec<_>, _>>()?;
+                // Un solo material se usa en las seis caras
+                let faces = std::array::from_fn(|face| resolved[face % resolved.len()].clone());
+                let mut cube = Cube::new(vector(*center), *size, faces, *is_water);
+                cube.casts_shadow = *casts_shadow;
+                objects.push(Box::new(cube));
+            }
+            ObjectFile::Sphere { center, radius, material } => {
+                let material = resolve(material, format!("objects[{}].material", i))?;
+                objects.push(Box::new(Sphere::new(vector(*center), *radius, material)));
+            }
+            ObjectFile::Plane { point, normal, material } => {
+                let material = resolve(material, format!("objects[{}].material", i))?;
+                objects.push(Box::new(Plane::new(vector(*point), vector(*normal), material)));
+            }
+        }
+    }
+
+    let lights = file.lights.iter().map(LightFile::to_light).collect();
+    Ok(Scene::new(objects, lights, file.camera.to_camera()))
+}
+
+fn validate_material(material: &Material, context: &str, warnings: &mut Vec<String>) {
+    let albedo = material.albedo;
+
+    if albedo.iter().any(|a| *a < 0.0 || !a.is_finite()) {
+        warnings.push(format!("{}: albedo {:?} has negative or non-finite components", context, albedo));
+    }
+    // La luz difusa, reflejada y refractada se reparten la energía que llega a la superficie
+    if albedo[0] + albedo[2] + albedo[3] > 1.0 {
+        warnings.push(format!("{}: diffuse + reflection + transparency albedo exceeds 1 ({:?})", context, albedo));
+    }
+    if material.refractive_index <= 0.0 {
+        warnings.push(format!("{}: invalid refractive index {}", context, material.refractive_index));
+    }
+}
@@ -0,0 +1,306 @@
+// Descripción serializable de una escena (cámara, luces y objetos), para guardarla y cargarla como
+// JSON. Un material puede ser el nombre de uno de la biblioteca o de la tabla `materials` del archivo,
+// o escribirse completo; completo solo se guardan sus propiedades numéricas, porque las texturas, el
+// ruido y demás capas no tienen una ruta asociada. Por eso los materiales con nombre se guardan por su
+// nombre y `MaterialWriter` cuenta las capas que se pierden de los demás.
+
+use std::collections::{BTreeMap, HashMap};
+
+use nalgebra_glm::Vec3;
+use serde::{Deserialize, Serialize};
+
+use crate::camera::{Camera, DEFAULT_FOV};
+use crate::color::Color;
+use crate::cube::Cube;
+use crate::intersect::RayIntersect;
+use crate::light::{Light, LightShape};
+use crate::material::{Material, MaterialLibrary};
+use crate::plane::Plane;
+use crate::sphere::Sphere;
+
+#[derive(Debug, Serialize, Deserialize)]
+pub(crate) struct SceneFile {
+    pub camera: CameraFile,
+    pub lights: Vec<LightFile>,
+    pub objects: Vec<ObjectFile>,
+    // Materiales con nombre definidos en el propio archivo; reemplazan a los de la biblioteca con el mismo nombre
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub materials: BTreeMap<String, MaterialFile>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub(crate) struct CameraFile {
+    pub eye: [f32; 3],
+    pub center: [f32; 3],
+    #[serde(default = "world_up")]
+    pub up: [f32; 3],
+    #[serde(default = "default_fov")]
+    pub fov: f32,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub(crate) struct LightFile {
+    pub position: [f32; 3],
+    pub color: [u8; 3],
+    pub intensity: f32,
+    #[serde(default)]
+    pub subtractive: bool,
+    #[serde(default)]
+    pub radius: f32,
+    #[serde(default)]
+    pub disk_normal: Option<[f32; 3]>,  // Normal del panel si la luz de área es un disco; None si es esférica
+    #[serde(default = "enabled")]
+    pub diffuse: bool,
+    #[serde(default = "enabled")]
+    pub specular: bool,
+}
+
+// Valores por defecto de los campos que un mapa escrito a mano puede omitir
+fn world_up() -> [f32; 3] {
+    [0.0, 1.0, 0.0]
+}
+
+fn default_fov() -> f32 {
+    DEFAULT_FOV
+}
+
+fn enabled() -> bool {
+    true
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub(crate) struct MaterialFile {
+    pub diffuse: [u8; 3],
+    pub specular: f32,
+    pub albedo: [f32; 4],
+    pub refractive_index: f32,
+    pub reflection_color: [u8; 3],
+    pub refraction_roughness: f32,
+}
+
+// Un material escrito completo o el nombre de uno ya definido ("glass", o uno de `materials`)
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(untagged)]
+pub(crate) enum MaterialRef {
+    Name(String),
+    Inline(MaterialFile),
+}
+
+// Cada objeto lleva su tipo en el campo "type"; los cubos tienen un material por cara, o uno solo para
+// las seis
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub(crate) enum ObjectFile {
+    Cube {
+        center: [f32; 3],
+        size: f32,
+        #[serde(default)]
+        is_water: bool,
+        #[serde(default = "enabled")]
+        casts_shadow: bool,
+        materials: Vec<MaterialRef>,
+    },
+    Sphere { center: [f32; 3], radius: f32, material: MaterialRef },
+    Plane { point: [f32; 3], normal: [f32; 3], material: MaterialRef },
+}
+
+fn vector(v: &Vec3) -> [f32; 3] {
+    [v.x, v.y, v.z]
+}
+
+fn rgb(color: &Color) -> [u8; 3] {
+    [color.r, color.g, color.b]
+}
+
+fn to_vec3([x, y, z]: [f32; 3]) -> Vec3 {
+    Vec3::new(x, y, z)
+}
+
+fn to_color([r, g, b]: [u8; 3]) -> Color {
+    Color::new(r, g, b)
+}
+
+impl CameraFile {
+    pub fn from_camera(camera: &Camera) -> Self {
+        CameraFile { eye: vector(&camera.eye), center: vector(&camera.center), up: vector(&camera.up), fov: camera.vertical_fov() }
+    }
+
+    pub fn to_camera(&self) -> Camera {
+        Camera { eye: to_vec3(self.eye), center: to_vec3(self.center), up: to_vec3(self.up), fov: self.fov, lens: None }
+    }
+}
+
+impl LightFile {
+    pub fn from_light(light: &Light) -> Self {
+        LightFile {
+            position: vector(&light.position),
+            color: rgb(&light.color),
+            intensity: light.intensity,
+            subtractive: light.subtractive,
+            radius: light.radius,
+            disk_normal: match light.shape {
+                LightShape::Sphere => None,
+                LightShape::Disk { normal } => Some(vector(&normal)),
+            },
+            diffuse: light.diffuse,
+            specular: light.specular,
+        }
+    }
+
+    pub fn to_light(&self) -> Light {
+        Light {
+            position: to_vec3(self.position),
+            color: to_color(self.color),
+            intensity: self.intensity,
+            subtractive: self.subtractive,
+            radius: self.radius,
+            shape: match self.disk_normal {
+                Some(normal) => LightShape::Disk { normal: to_vec3(normal) },
+                None => LightShape::Sphere,
+            },
+            diffuse: self.diffuse,
+            specular: self.specular,
+        }
+    }
+}
+
+impl MaterialFile {
+    // Solo las propiedades numéricas; `lost_fields` dice qué más tenía el material
+    pub fn from_material(material: &Material) -> Self {
+        MaterialFile {
+            diffuse: rgb(&material.diffuse),
+            specular: material.specular,
+            albedo: material.albedo,
+            refractive_index: material.refractive_index,
+            reflection_color: rgb(&material.reflection_color),
+            refraction_roughness: material.refraction_roughness,
+        }
+    }
+
+    pub fn to_material(&self) -> Material {
+        Material {
+            diffuse: to_color(self.diffuse),
+            specular: self.specular,
+            albedo: self.albedo,
+            refractive_index: self.refractive_index,
+            reflection_color: to_color(self.reflection_color),
+            refraction_roughness: self.refraction_roughness,
+            ..Material::black()
+        }
+    }
+
+    // Las capas del material que `from_material` no guarda
+    pub fn lost_fields(material: &Material) -> Vec<&'static str> {
+        [
+            ("texture", material.texture.is_some()),
+            ("emission_texture", material.emission_texture.is_some()),
+            ("blend", material.blend.is_some()),
+            ("noise", material.noise.is_some()),
+            ("thin_film", material.thin_film.is_some()),
+            ("anisotropy", material.anisotropy.is_some()),
+            ("detail", material.detail.is_some()),
+        ]
+        .into_iter()
+        .filter_map(|(field, present)| present.then_some(field))
+        .collect()
+    }
+}
+
+// Convierte los materiales de los objetos que se guardan. Los que tienen nombre se escriben por nombre;
+// si el formato los describe completos también van a `table`, para que el archivo cargue sin la
+// biblioteca, y si no (los texturizados de la isla) se confía en que la biblioteca los tenga al cargar.
+// Los demás se escriben completos y lo que no se pudo guardar se anota en `lost_fields`.
+//
+// Una instancia con nombre cuyas propiedades numéricas ya no son las del original (el de `library`, o
+// si no está ahí el primero que se escribió con ese nombre) es una copia retocada: si el formato la
+// describe completa se guarda en `table` como `nombre#2`, `nombre#3`..., y si no, se escribe por el
+// nombre original y se anota en `lost_tweaks`, porque al cargar volvería a ser el de la biblioteca.
+pub(crate) struct MaterialWriter<'a> {
+    pub table: BTreeMap<String, MaterialFile>,
+    pub lost_fields: Vec<&'static str>,
+    pub lost_tweaks: Vec<String>,
+    library: &'a MaterialLibrary,
+    originals: HashMap<String, MaterialFile>,
+}
+
+impl<'a> MaterialWriter<'a> {
+    pub fn new(library: &'a MaterialLibrary) -> Self {
+        MaterialWriter {
+            table: BTreeMap::new(),
+            lost_fields: Vec::new(),
+            lost_tweaks: Vec::new(),
+            library,
+            originals: HashMap::new(),
+        }
+    }
+
+    pub fn write(&mut self, material: &Material) -> MaterialRef {
+        let lost = MaterialFile::lost_fields(material);
+        let file = MaterialFile::from_material(material);
+        let Some(name) = &material.name else {
+            self.lost_fields.extend(lost);
+            return MaterialRef::Inline(file);
+        };
+
+        let library = self.library;
+        let original = self
+            .originals
+            .entry(name.to_string())
+            .or_insert_with(|| library.get(name).map(MaterialFile::from_material).unwrap_or_else(|| file.clone()));
+        if *original == file {
+            if lost.is_empty() {
+                self.table.insert(name.to_string(), file);
+            }
+            return MaterialRef::Name(name.to_string());
+        }
+        if !lost.is_empty() {
+            self.lost_tweaks.push(name.to_string());
+            return MaterialRef::Name(name.to_string());
+        }
+
+        // Se reutiliza la copia retocada igual que ya se haya guardado, o se le da el siguiente número libre
+        let mut copy = 2;
+        loop {
+            let key = format!("{}#{}", name, copy);
+            match self.table.get(&key) {
+                Some(existing) if *existing != file => copy += 1,
+                Some(_) => return MaterialRef::Name(key),
+                None => {
+                    self.table.insert(key.clone(), file);
+                    return MaterialRef::Name(key);
+                }
+            }
+        }
+    }
+}
+
+impl ObjectFile {
+    // None para los tipos de objeto que el formato todavía no describe (grupos, instancias, etc.)
+    pub fn from_object(object: &dyn RayIntersect, materials: &mut MaterialWriter) -> Option<Self> {
+        let any = object.as_any();
+        if let Some(cube) = any.downcast_ref::<Cube>() {
+            return Some(ObjectFile::Cube {
+                center: vector(&cube.center()),
+                size: cube.size(),
+                is_water: cube.is_water,
+                casts_shadow: cube.casts_shadow,
+                materials: cube.materials.iter().map(|material| materials.write(material)).collect(),
+            });
+        }
+        if let Some(sphere) = any.downcast_ref::<Sphere>() {
+            return Some(ObjectFile::Sphere {
+                center: vector(&sphere.center),
+                radius: sphere.radius,
+                material: materials.write(&sphere.material),
+            });
+        }
+        if let Some(plane) = any.downcast_ref::<Plane>() {
+            return Some(ObjectFile::Plane {
+                point: vector(&plane.point),
+                normal: vector(&plane.normal),
+                material: materials.write(&plane.material),
+            });
+        }
+        None
+    }
+}
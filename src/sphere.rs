@@ -0,0 +1,94 @@
+use std::any::Any;
+use std::f32::consts::PI;
+
+use nalgebra_glm::Vec3;
+use crate::intersect::{Intersect, RayIntersect};
+use crate::material::Material;
+use crate::aabb::Aabb;
+
+pub struct Sphere {
+    pub center: Vec3,
+    pub radius: f32,
+    pub material: Material,
+}
+
+impl Sphere {
+    pub fn new(center: Vec3, radius: f32, material: Material) -> Self {
+        Sphere { center, radius, material }
+    }
+
+    // Coordenadas UV esféricas a partir de la normal: u recorre la longitud, v va de 0 (abajo) a 1 (arriba).
+    // En el meridiano de -x la longitud salta de pi a -pi; u queda en [0, 1), así los dos lados de la
+    // costura leen columnas vecinas de la textura. El nivel de mip no nota el salto porque sale del cono
+    // del rayo y de `uv_scale`, no de la diferencia de u entre píxeles vecinos.
+    pub fn get_uv(normal: &Vec3) -> (f32, f32) {
+        let u = (0.5 + normal.z.atan2(normal.x) / (2.0 * PI)).rem_euclid(1.0);
+        let v = 0.5 + normal.y.clamp(-1.0, 1.0).asin() / PI;
+        (u, v)
+    }
+
+    // Distancias donde el rayo entra (t1) y sale (t2) de la esfera, o None si no la cruza
+    fn roots(&self, ray_origin: &Vec3, ray_direction: &Vec3) -> Option<(f32, f32)> {
+        let oc = ray_origin - self.center;
+        let a = ray_direction.dot(ray_direction);
+        let half_b = oc.dot(ray_direction);
+        let c = oc.dot(&oc) - self.radius * self.radius;
+
+        let discriminant = half_b * half_b - a * c;
+        if discriminant < 0.0 {
+            return None;
+        }
+        let sqrt_discriminant = discriminant.sqrt();
+        Some(((-half_b - sqrt_discriminant) / a, (-half_b + sqrt_discriminant) / a))
+    }
+}
+
+impl RayIntersect for Sphere {
+    fn ray_intersect(&self, ray_origin: &Vec3, ray_direction: &Vec3, t_min: f32, t_max: f32) -> Intersect {
+        let Some((t1, t2)) = self.roots(ray_origin, ray_direction) else {
+            return Intersect::empty();
+        };
+
+        // Igual que en el cubo: la entrada si está en el rango, si no la salida (el rayo empieza dentro)
+        let t_hit = if t1 >= t_min && t1 <= t_max {
+            t1
+        } else if t2 >= t_min && t2 <= t_max {
+            t2
+        } else {
+            return Intersect::empty();
+        };
+
+        let point = ray_origin + ray_direction * t_hit;
+        let normal = (point - self.center).normalize();
+        let (u, v) = Sphere::get_uv(&normal);
+
+        // v recorre media circunferencia (pi * r), la dirección en que la textura está más comprimida
+        Intersect::new(point, normal, t_hit, self.material.clone(), u, v, t_hit == t1).with_uv_scale(1.0 / (PI * self.radius))
+    }
+
+    fn intersect_any(&self, ray_origin: &Vec3, ray_direction: &Vec3, t_min: f32, t_max: f32) -> Option<f32> {
+        let (t1, t2) = self.roots(ray_origin, ray_direction)?;
+        [t1, t2].into_iter().find(|t| *t >= t_min && *t <= t_max)
+    }
+
+    fn ray_span(&self, ray_origin: &Vec3, ray_direction: &Vec3) -> Option<(f32, f32)> {
+        let (t1, t2) = self.roots(ray_origin, ray_direction)?;
+        if t2 < 0.0 {
+            return None;
+        }
+        Some((t1.max(0.0), t2))
+    }
+
+    fn bounding_box(&self) -> Aabb {
+        let extent = Vec3::repeat(self.radius);
+        Aabb::new(self.center - extent, self.center + extent)
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        self
+    }
+}
@@ -0,0 +1,71 @@
+use nalgebra_glm::Vec3;
+
+use crate::cube::Cube;
+use crate::intersect::RayIntersect;
+use crate::material::Material;
+
+// Altura máxima de una columna, en cubos, para un píxel blanco del mapa de alturas
+pub const MAX_TERRAIN_HEIGHT: usize = 8;
+
+// Materiales asignados según la altura relativa de cada columna
+pub struct HeightBands {
+    pub water: Material,  // Por debajo del 15% de la altura máxima
+    pub sand: Material,   // Hasta el 30%
+    pub grass: Material,  // Hasta el 70%
+    pub rock: Material,   // Por encima
+}
+
+impl HeightBands {
+    fn material_for(&self, height: f32) -> (&Material, bool) {
+        if height < 0.15 {
+            (&self.water, true)
+        } else if height < 0.3 {
+            (&self.sand, false)
+        } else if height < 0.7 {
+            (&self.grass, false)
+        } else {
+            (&self.rock, false)
+        }
+    }
+}
+
+// Construye un terreno de columnas de cubos a partir de un mapa de alturas en escala de grises.
+// Cada píxel es una celda de `scale` x `scale` y su brillo define cuántos cubos se apilan.
+// Solo se crean los cubos expuestos: los que quedan tapados por los vecinos se omiten.
+pub fn load_heightmap_terrain(path: &str, scale: f32, bands: &HeightBands) -> Vec<Box<dyn RayIntersect>> {
+    let heightmap = image::open(path).expect("Failed to load heightmap").to_luma8();
+    let (width, depth) = (heightmap.width() as usize, heightmap.height() as usize);
+
+    let brightness = |x: usize, z: usize| heightmap.get_pixel(x as u32, z as u32)[0] as f32 / 255.0;
+    let column_height = |x: usize, z: usize| ((brightness(x, z) * MAX_TERRAIN_HEIGHT as f32).round() as usize).max(1);
+
+    let mut objects: Vec<Box<dyn RayIntersect>> = Vec::new();
+
+    for z in 0..depth {
+        for x in 0..width {
+            let height = column_height(x, z);
+            let (material, is_water) = bands.material_for(brightness(x, z));
+
+            // La columna más baja de los vecinos marca desde dónde quedan caras visibles
+            let lowest_neighbor = [
+                (x.wrapping_sub(1), z),
+                (x + 1, z),
+                (x, z.wrapping_sub(1)),
+                (x, z + 1),
+            ]
+            .iter()
+            .map(|&(nx, nz)| if nx < width && nz < depth { column_height(nx, nz) } else { 0 })
+            .min()
+            .unwrap_or(0);
+
+            for level in lowest_neighbor.min(height - 1)..height {
+                let center = Vec3::new(x as f32 * scale, level as f32 * scale, -(z as f32) * scale);
+                let mut cube = Cube::uniform(center, scale, material.clone());
+                cube.is_water = is_water;
+                objects.push(Box::new(cube));
+            }
+        }
+    }
+
+    objects
+}
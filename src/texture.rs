@@ -1,3 +1,7 @@
+use std::collections::HashMap;
+use std::sync::{Arc, LazyLock};
+
+use image::GenericImageView;
 use crate::color::Color;
 
 #[derive(Debug, Clone)]  // Derivamos Debug para poder imprimir texturas
@@ -5,14 +9,139 @@ pub struct Texture {
     pub width: usize,
     pub height: usize,
     pub data: Vec<Color>,  // Datos de la imagen
+    pub mips: Vec<Texture>,  // Versiones reducidas a la mitad, de la más grande a la de 1x1; vacío si no se generaron
 }
 
 impl Texture {
     pub fn new(width: usize, height: usize, data: Vec<Color>) -> Self {
-        Texture { width, height, data }
+        Texture { width, height, data, mips: Vec::new() }
+    }
+
+    // La misma textura con su cadena de mips: cada nivel promedia bloques de 2x2 texels del anterior,
+    // hasta llegar a 1x1. Ocupa un tercio más de memoria y quita el centelleo de las texturas lejanas.
+    pub fn with_mipmaps(mut self) -> Self {
+        let mut mips: Vec<Texture> = Vec::new();
+        let mut level = self.half_size();
+        while let Some(next) = level {
+            level = next.half_size();
+            mips.push(next);
+        }
+        self.mips = mips;
+        self
+    }
+
+    // Reducción a la mitad con filtro de caja; None si la textura ya es de 1x1. Cada texel promedia un
+    // bloque de 2x2 del nivel anterior; en un lado impar el último bloque toma 3 texels para no perder
+    // la última columna o fila, y en un lado de 1 el bloque es de 1.
+    fn half_size(&self) -> Option<Texture> {
+        if self.width == 1 && self.height == 1 {
+            return None;
+        }
+        let width = (self.width / 2).max(1);
+        let height = (self.height / 2).max(1);
+        // Texels del nivel anterior que cubre el texel `i` de un lado de `half` texels
+        let block = |i: usize, full: usize, half: usize| (i * full / half)..((i + 1) * full / half);
+        let mut data = Vec::with_capacity(width * height);
+        for y in 0..height {
+            for x in 0..width {
+                let mut sum = [0u32; 3];
+                let mut count = 0;
+                for source_y in block(y, self.height, height) {
+                    for source_x in block(x, self.width, width) {
+                        let texel = self.get_color(source_x, source_y);
+                        sum[0] += texel.r as u32;
+                        sum[1] += texel.g as u32;
+                        sum[2] += texel.b as u32;
+                        count += 1;
+                    }
+                }
+                data.push(Color::new((sum[0] / count) as u8, (sum[1] / count) as u8, (sum[2] / count) as u8));
+            }
+        }
+        Some(Texture::new(width, height, data))
+    }
+
+    // Textura de 1x1 con un solo color: muestrearla en cualquier (u, v) da ese color, así un material
+    // de color plano se comporta igual que uno texturizado
+    pub fn from_color(color: Color) -> Self {
+        Texture::new(1, 1, vec![color])
+    }
+
+    // Textura blanca de 1x1 que muestrean los materiales sin imagen propia: su color sale de
+    // `Material::diffuse`, que tiñe la textura. Es una referencia y no un `Arc` para no tocar un
+    // contador compartido por todos los hilos en cada impacto.
+    pub fn white() -> &'static Texture {
+        static WHITE: LazyLock<Texture> = LazyLock::new(|| Texture::from_color(Color::new(255, 255, 255)));
+        &WHITE
     }
 
     pub fn get_color(&self, x: usize, y: usize) -> Color {
         self.data[y * self.width + x]
     }
+
+    // Muestrea la textura en coordenadas UV, repitiéndola fuera del rango [0, 1]. El módulo se toma en
+    // punto flotante: convertir primero a usize llevaría todo valor negativo a la columna 0. El `min`
+    // cubre el redondeo de `rem_euclid`, que con valores negativos diminutos puede dar el ancho exacto.
+    pub fn sample(&self, u: f32, v: f32) -> Color {
+        let (width, height) = (self.width as f32, self.height as f32);
+        let tex_x = ((u * width).rem_euclid(width) as usize).min(self.width - 1);
+        let tex_y = (((1.0 - v) * height).rem_euclid(height) as usize).min(self.height - 1);
+        self.get_color(tex_x, tex_y)
+    }
+
+    // Nivel de mip que corresponde a un rayo que cubre `footprint` unidades de UV: 0 mientras el rayo
+    // cubra a lo sumo un texel, y uno más cada vez que cubre el doble
+    pub fn mip_level(&self, footprint: f32) -> f32 {
+        let texels = footprint * self.width.max(self.height) as f32;
+        if texels > 1.0 { texels.log2() } else { 0.0 }
+    }
+
+    // Muestrea la textura para un rayo que cubre `footprint` unidades de UV (0 para un rayo sin ancho),
+    // en el nivel de mip más cercano a `mip_level`. Sin mips se lee siempre la imagen completa.
+    pub fn sample_footprint(&self, u: f32, v: f32, footprint: f32) -> Color {
+        let level = (self.mip_level(footprint).round() as usize).min(self.mips.len());
+        match level {
+            0 => self.sample(u, v),
+            level => self.mips[level - 1].sample(u, v),
+        }
+    }
+}
+
+
+// Carga una imagen como textura, volteada verticalmente para que v = 0 quede abajo
+pub fn load_texture(filename: &str) -> Texture {
+    let img = image::open(filename).expect("Failed to load texture");
+    let (width, height) = img.dimensions();
+    let mut data = Vec::new();
+
+    for y in (0..height).rev() {  // Flip vertically
+        for x in 0..width {
+            let pixel = img.get_pixel(x, y);
+            data.push(Color::new(pixel[0], pixel[1], pixel[2]));
+        }
+    }
+
+    Texture::new(width as usize, height as usize, data)
+}
+
+
+// Carga cada archivo una sola vez y entrega referencias compartidas, para que cientos de caras
+// que usan la misma imagen no dupliquen sus píxeles
+#[derive(Default)]
+pub struct TextureCache {
+    textures: HashMap<String, Arc<Texture>>,
+}
+
+impl TextureCache {
+    pub fn new() -> Self {
+        TextureCache::default()
+    }
+
+    // Devuelve la textura de `path`, cargándola (con sus mips) solo la primera vez que se pide
+    pub fn get(&mut self, path: &str) -> Arc<Texture> {
+        self.textures
+            .entry(path.to_string())
+            .or_insert_with(|| Arc::new(load_texture(path).with_mipmaps()))
+            .clone()
+    }
 }
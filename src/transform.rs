@@ -0,0 +1,125 @@
+use std::any::Any;
+use std::sync::Arc;
+
+use nalgebra_glm::{Mat3, Mat4, Vec3};
+use crate::intersect::{Intersect, RayIntersect};
+use crate::aabb::Aabb;
+
+// Traslación, rotación y escala de un objeto, guardadas como matriz junto con su inversa
+#[derive(Debug, Clone, Copy)]
+pub struct Transform {
+    matrix: Mat4,
+    inverse: Mat4,
+    normal_matrix: Mat3,  // Inversa transpuesta de la parte lineal: lleva normales locales al mundo
+}
+
+impl Transform {
+    // Primero escala, después rota (ángulos de Euler en grados, aplicados en orden X, Y, Z) y al final traslada
+    pub fn new(translation: Vec3, rotation: Vec3, scale: Vec3) -> Self {
+        let rotate = |angle: f32, axis: Vec3| nalgebra_glm::rotation(angle.to_radians(), &axis);
+        let matrix = nalgebra_glm::translation(&translation)
+            * rotate(rotation.z, Vec3::z())
+            * rotate(rotation.y, Vec3::y())
+            * rotate(rotation.x, Vec3::x())
+            * nalgebra_glm::scaling(&scale);
+        Transform::from_matrix(matrix)
+    }
+
+    pub fn identity() -> Self {
+        Transform::from_matrix(Mat4::identity())
+    }
+
+    // `matrix` debe ser invertible (sin escalas nulas)
+    pub fn from_matrix(matrix: Mat4) -> Self {
+        let inverse = nalgebra_glm::inverse(&matrix);
+        let normal_matrix = nalgebra_glm::transpose(&nalgebra_glm::mat4_to_mat3(&inverse));
+        Transform { matrix, inverse, normal_matrix }
+    }
+
+    pub fn point(&self, point: &Vec3) -> Vec3 {
+        (self.matrix * point.push(1.0)).xyz()
+    }
+
+    pub fn inverse_point(&self, point: &Vec3) -> Vec3 {
+        (self.inverse * point.push(1.0)).xyz()
+    }
+
+    pub fn inverse_direction(&self, direction: &Vec3) -> Vec3 {
+        (self.inverse * direction.push(0.0)).xyz()
+    }
+
+    pub fn normal(&self, normal: &Vec3) -> Vec3 {
+        (self.normal_matrix * normal).normalize()
+    }
+}
+
+// Cualquier primitiva con una transformación: el rayo se lleva al espacio local con la inversa, la
+// primitiva lo intersecta como siempre y el punto y la normal vuelven al mundo. La dirección local
+// no se normaliza, así que la distancia `t` vale lo mismo en ambos espacios; las UV son las locales.
+pub struct Transformed<T: RayIntersect> {
+    pub object: T,
+    pub transform: Transform,
+}
+
+// Una geometría compartida colocada con su propia transformación: cien árboles iguales en distintas
+// posiciones guardan una sola copia de la geometría y solo una `Transform` por instancia
+pub type Instance = Transformed<Arc<dyn RayIntersect>>;
+
+impl<T: RayIntersect> Transformed<T> {
+    pub fn new(object: T, transform: Transform) -> Self {
+        Transformed { object, transform }
+    }
+
+    fn local_ray(&self, ray_origin: &Vec3, ray_direction: &Vec3) -> (Vec3, Vec3) {
+        (self.transform.inverse_point(ray_origin), self.transform.inverse_direction(ray_direction))
+    }
+}
+
+impl<T: RayIntersect + 'static> RayIntersect for Transformed<T> {
+    fn ray_intersect(&self, ray_origin: &Vec3, ray_direction: &Vec3, t_min: f32, t_max: f32) -> Intersect {
+        let (local_origin, local_direction) = self.local_ray(ray_origin, ray_direction);
+        let mut intersect = self.object.ray_intersect(&local_origin, &local_direction, t_min, t_max);
+        if intersect.is_intersecting {
+            intersect.point = ray_origin + ray_direction * intersect.distance;
+            intersect.normal = self.transform.normal(&intersect.normal);
+            // La densidad de UV es por unidad local: se pasa al mundo con lo que la transformación estira el rayo
+            intersect.uv_scale *= local_direction.norm() / ray_direction.norm();
+        }
+        intersect
+    }
+
+    fn intersect_any(&self, ray_origin: &Vec3, ray_direction: &Vec3, t_min: f32, t_max: f32) -> Option<f32> {
+        let (local_origin, local_direction) = self.local_ray(ray_origin, ray_direction);
+        self.object.intersect_any(&local_origin, &local_direction, t_min, t_max)
+    }
+
+    fn ray_span(&self, ray_origin: &Vec3, ray_direction: &Vec3) -> Option<(f32, f32)> {
+        let (local_origin, local_direction) = self.local_ray(ray_origin, ray_direction);
+        self.object.ray_span(&local_origin, &local_direction)
+    }
+
+    fn casts_shadow(&self) -> bool {
+        self.object.casts_shadow()
+    }
+
+    // Caja alineada a los ejes que contiene las 8 esquinas transformadas de la caja local
+    fn bounding_box(&self) -> Aabb {
+        let local = self.object.bounding_box();
+        if !local.is_finite() {
+            return Aabb::new(Vec3::repeat(f32::NEG_INFINITY), Vec3::repeat(f32::INFINITY));
+        }
+
+        let corners = local.corners().map(|corner| self.transform.point(&corner));
+        let min = corners.iter().fold(Vec3::repeat(f32::INFINITY), |min, corner| min.inf(corner));
+        let max = corners.iter().fold(Vec3::repeat(f32::NEG_INFINITY), |max, corner| max.sup(corner));
+        Aabb::new(min, max)
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        self
+    }
+}
@@ -0,0 +1,105 @@
+use std::any::Any;
+
+use nalgebra_glm::Vec3;
+use crate::intersect::{Intersect, RayIntersect};
+use crate::material::Material;
+use crate::aabb::Aabb;
+
+// Triángulo con vértices en sentido antihorario vistos desde afuera. Las normales por vértice (si las
+// hay) se interpolan para sombrear la malla suave; sin ellas se usa la normal de la cara.
+pub struct Triangle {
+    pub vertices: [Vec3; 3],
+    pub normals: Option<[Vec3; 3]>,
+    pub uvs: Option<[(f32, f32); 3]>,
+    pub material: Material,
+}
+
+impl Triangle {
+    pub fn new(vertices: [Vec3; 3], material: Material) -> Self {
+        Triangle { vertices, normals: None, uvs: None, material }
+    }
+
+    // Normal de la cara según el orden de los vértices
+    pub fn face_normal(&self) -> Vec3 {
+        let [a, b, c] = self.vertices;
+        (b - a).cross(&(c - a)).normalize()
+    }
+
+    // Distancia y coordenadas baricéntricas (de los vértices 1 y 2) del cruce con el rayo, por el
+    // método de Möller-Trumbore; None si el rayo es paralelo o pasa fuera del triángulo
+    fn hit(&self, ray_origin: &Vec3, ray_direction: &Vec3) -> Option<(f32, f32, f32)> {
+        let [a, b, c] = self.vertices;
+        let edge1 = b - a;
+        let edge2 = c - a;
+        let p = ray_direction.cross(&edge2);
+        let determinant = edge1.dot(&p);
+        if determinant.abs() < 1e-8 {
+            return None;
+        }
+
+        let inverse = 1.0 / determinant;
+        let s = ray_origin - a;
+        let beta = s.dot(&p) * inverse;
+        if !(0.0..=1.0).contains(&beta) {
+            return None;
+        }
+        let q = s.cross(&edge1);
+        let gamma = ray_direction.dot(&q) * inverse;
+        if gamma < 0.0 || beta + gamma > 1.0 {
+            return None;
+        }
+        Some((edge2.dot(&q) * inverse, beta, gamma))
+    }
+
+    // Unidades de UV por unidad de mundo: raíz del cociente entre el área en UV y el área real
+    fn uv_scale(&self, uvs: &[(f32, f32); 3]) -> f32 {
+        let [a, b, c] = self.vertices;
+        let world_area = (b - a).cross(&(c - a)).norm();
+        let [(u0, v0), (u1, v1), (u2, v2)] = *uvs;
+        let uv_area = ((u1 - u0) * (v2 - v0) - (u2 - u0) * (v1 - v0)).abs();
+        if world_area > 0.0 && uv_area > 0.0 { (uv_area / world_area).sqrt() } else { 1.0 }
+    }
+}
+
+impl RayIntersect for Triangle {
+    fn ray_intersect(&self, ray_origin: &Vec3, ray_direction: &Vec3, t_min: f32, t_max: f32) -> Intersect {
+        let (t_hit, beta, gamma) = match self.hit(ray_origin, ray_direction) {
+            Some((t, beta, gamma)) if t >= t_min && t <= t_max => (t, beta, gamma),
+            _ => return Intersect::empty(),
+        };
+        let alpha = 1.0 - beta - gamma;
+
+        let point = ray_origin + ray_direction * t_hit;
+        let face_normal = self.face_normal();
+        let normal = match &self.normals {
+            Some([na, nb, nc]) => (na * alpha + nb * beta + nc * gamma).normalize(),
+            None => face_normal,
+        };
+        let (u, v) = match &self.uvs {
+            Some([(u0, v0), (u1, v1), (u2, v2)]) => (u0 * alpha + u1 * beta + u2 * gamma, v0 * alpha + v1 * beta + v2 * gamma),
+            None => (beta, gamma),
+        };
+        let uv_scale = self.uvs.as_ref().map_or(1.0, |uvs| self.uv_scale(uvs));
+        // El rayo llega desde afuera si viaja en contra de la normal de la cara
+        let front_face = ray_direction.dot(&face_normal) < 0.0;
+
+        Intersect::new(point, normal, t_hit, self.material.clone(), u, v, front_face).with_uv_scale(uv_scale)
+    }
+
+    fn intersect_any(&self, ray_origin: &Vec3, ray_direction: &Vec3, t_min: f32, t_max: f32) -> Option<f32> {
+        self.hit(ray_origin, ray_direction).map(|(t, _, _)| t).filter(|t| *t >= t_min && *t <= t_max)
+    }
+
+    fn bounding_box(&self) -> Aabb {
+        let [a, b, c] = self.vertices;
+        Aabb::new(a.inf(&b).inf(&c), a.sup(&b).sup(&c))
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        self
+    }
+}
@@ -0,0 +1,86 @@
+use rayon::prelude::*;
+
+use crate::gbuffer::GBuffer;
+
+// Función para escalar el framebuffer de baja resolución al tamaño completo (vecino más cercano, lo más rápido)
+pub fn upscale_framebuffer(
+    low_res_buffer: &[u32],
+    low_width: usize,
+    low_height: usize,
+    high_width: usize,
+    high_height: usize,
+) -> Vec<u32> {
+    let mut high_res_buffer = vec![0; high_width * high_height];
+
+    for y in 0..high_height {
+        let src_y = y * low_height / high_height;
+        for x in 0..high_width {
+            let src_x = x * low_width / high_width;
+            let src_index = src_y * low_width + src_x;
+            let dst_index = y * high_width + x;
+            high_res_buffer[dst_index] = low_res_buffer[src_index];
+        }
+    }
+
+    high_res_buffer
+}
+
+
+// Tolerancias de la guía geométrica: cuánto pueden diferir la normal y la profundidad (relativa a la
+// distancia a la cámara) de un píxel de alta resolución y una muestra de baja antes de ignorarla
+const NORMAL_POWER: i32 = 16;
+const SIGMA_DEPTH: f32 = 0.05;
+
+// Escalado guiado por geometría (joint bilateral upsampling): cada píxel de alta resolución mezcla
+// las 4 muestras de baja resolución que lo rodean, con pesos bilineales multiplicados por lo mucho
+// que se parecen su normal y su profundidad a las del píxel según el G-buffer de alta resolución.
+// Así los bordes entre objetos quedan nítidos en vez de escalonados (vecino más cercano) o borrosos
+// (bilineal). Si ninguna muestra se parece, se usa la más cercana.
+pub fn upscale_edge_aware(low_res_buffer: &[u32], low_gbuffer: &GBuffer, high_gbuffer: &GBuffer) -> Vec<u32> {
+    let (low_width, low_height) = (low_gbuffer.width, low_gbuffer.height);
+    let (high_width, high_height) = (high_gbuffer.width, high_gbuffer.height);
+
+    (0..high_width * high_height)
+        .into_par_iter()
+        .map(|index| {
+            // Los rayos salen de la esquina de cada píxel, así que las esquinas coinciden en ambas resoluciones
+            let low_x = (index % high_width) as f32 * low_width as f32 / high_width as f32;
+            let low_y = (index / high_width) as f32 * low_height as f32 / high_height as f32;
+            let (x0, y0) = (low_x.floor() as usize, low_y.floor() as usize);
+            let (tx, ty) = (low_x - x0 as f32, low_y - y0 as f32);
+
+            let mut sum = [0.0f32; 3];
+            let mut weight_sum = 0.0;
+            for (dx, dy, bilinear) in [(0, 0, (1.0 - tx) * (1.0 - ty)), (1, 0, tx * (1.0 - ty)), (0, 1, (1.0 - tx) * ty), (1, 1, tx * ty)] {
+                let sample = (y0 + dy).min(low_height - 1) * low_width + (x0 + dx).min(low_width - 1);
+                let weight = bilinear * guide_weight(high_gbuffer, index, low_gbuffer, sample);
+                let pixel = low_res_buffer[sample];
+                for (c, shift) in [16, 8, 0].into_iter().enumerate() {
+                    sum[c] += ((pixel >> shift) & 0xFF) as f32 * weight;
+                }
+                weight_sum += weight;
+            }
+
+            if weight_sum < 1e-4 {
+                let nearest = (low_y.round() as usize).min(low_height - 1) * low_width + (low_x.round() as usize).min(low_width - 1);
+                return low_res_buffer[nearest];
+            }
+            let [r, g, b] = sum.map(|channel| (channel / weight_sum).round().min(255.0) as u32);
+            (r << 16) | (g << 8) | b
+        })
+        .collect()
+}
+
+// Parecido geométrico entre el píxel `high` y la muestra `low`; el fondo solo se mezcla con fondo
+fn guide_weight(high_gbuffer: &GBuffer, high: usize, low_gbuffer: &GBuffer, low: usize) -> f32 {
+    match (high_gbuffer.is_background(high), low_gbuffer.is_background(low)) {
+        (true, true) => 1.0,
+        (false, false) => {
+            let normal = high_gbuffer.normals[high].dot(&low_gbuffer.normals[low]).max(0.0).powi(NORMAL_POWER);
+            let depth = high_gbuffer.depths[high];
+            let difference = (depth - low_gbuffer.depths[low]).abs() / (SIGMA_DEPTH * depth);
+            normal * (-difference * difference).exp()
+        }
+        _ => 0.0,
+    }
+}
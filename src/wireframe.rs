@@ -0,0 +1,84 @@
+use crate::camera::Camera;
+use crate::intersect::RayIntersect;
+use crate::aabb::Aabb;
+use crate::bvh::flatten;
+
+// Dibuja las aristas de la caja delimitadora de cada objeto sobre el framebuffer
+pub fn draw_bounding_boxes(
+    framebuffer: &mut [u32],
+    width: usize,
+    height: usize,
+    objects: &[Box<dyn RayIntersect>],
+    camera: &Camera,
+    color: u32,
+) {
+    // Una caja por objeto, también para los que están dentro de una `Bvh`
+    for object in flatten(objects) {
+        // Las cajas infinitas (planos) no tienen aristas que dibujar
+        let aabb = object.bounding_box();
+        if !aabb.is_finite() {
+            continue;
+        }
+        let corners = aabb.corners();
+        let projected = corners.map(|corner| camera.project(&corner, width, height));
+
+        for (a, b) in Aabb::edges() {
+            // Las aristas con algún extremo detrás de la cámara no se dibujan
+            if let (Some(start), Some(end)) = (projected[a], projected[b]) {
+                draw_line(framebuffer, width, height, start, end, color);
+            }
+        }
+    }
+}
+
+// Mira en forma de cruz centrada en `center`, con brazos de `size` píxeles
+pub fn draw_crosshair(framebuffer: &mut [u32], width: usize, height: usize, center: (f32, f32), size: f32, color: u32) {
+    let (x, y) = center;
+    draw_line(framebuffer, width, height, (x - size, y), (x + size, y), color);
+    draw_line(framebuffer, width, height, (x, y - size), (x, y + size), color);
+}
+
+// Línea de Bresenham en espacio de pantalla; los píxeles fuera del framebuffer se descartan
+pub fn draw_line(
+    framebuffer: &mut [u32],
+    width: usize,
+    height: usize,
+    start: (f32, f32),
+    end: (f32, f32),
+    color: u32,
+) {
+    // Evita recorrer líneas enormes cuando un extremo queda muy lejos de la pantalla
+    let limit = 4.0 * width.max(height) as f32;
+    if [start.0, start.1, end.0, end.1].iter().any(|c| c.abs() > limit) {
+        return;
+    }
+
+    let (mut x0, mut y0) = (start.0 as i32, start.1 as i32);
+    let (x1, y1) = (end.0 as i32, end.1 as i32);
+
+    let dx = (x1 - x0).abs();
+    let dy = -(y1 - y0).abs();
+    let step_x = if x0 < x1 { 1 } else { -1 };
+    let step_y = if y0 < y1 { 1 } else { -1 };
+    let mut error = dx + dy;
+
+    loop {
+        if x0 >= 0 && y0 >= 0 && (x0 as usize) < width && (y0 as usize) < height {
+            framebuffer[y0 as usize * width + x0 as usize] = color;
+        }
+
+        if x0 == x1 && y0 == y1 {
+            break;
+        }
+
+        let doubled = 2 * error;
+        if doubled >= dy {
+            error += dy;
+            x0 += step_x;
+        }
+        if doubled <= dx {
+            error += dx;
+            y0 += step_y;
+        }
+    }
+}
@@ -0,0 +1,111 @@
+use std::sync::Arc;
+
+use nalgebra_glm::Vec3;
+use ray_tracing::accumulation::Accumulator;
+use ray_tracing::camera::{Camera, DEFAULT_FOV};
+use ray_tracing::color::Color;
+use ray_tracing::intersect::RayIntersect;
+use ray_tracing::light::Light;
+use ray_tracing::material::Material;
+use ray_tracing::plane::Plane;
+use ray_tracing::render::{render, RenderConfig};
+use ray_tracing::sphere::Sphere;
+use ray_tracing::texture::Texture;
+
+const WIDTH: usize = 24;
+const HEIGHT: usize = 18;
+const SAMPLES: u32 = 8;
+
+// Esfera sobre el fondo: las siluetas cambian con el desplazamiento de cada muestra
+fn scene() -> (Vec<Box<dyn RayIntersect>>, Vec<Light>, Camera) {
+    let objects: Vec<Box<dyn RayIntersect>> = vec![Box::new(Sphere::new(Vec3::new(0.0, 0.0, -4.0), 1.2, Material::matte_white()))];
+    let lights = vec![Light::new(Vec3::new(3.0, 4.0, 2.0), Color::new(255, 255, 255), 1.0)];
+    let camera = Camera {
+        eye: Vec3::zeros(),
+        center: Vec3::new(0.0, 0.0, -1.0),
+        up: Vec3::new(0.0, 1.0, 0.0),
+        fov: DEFAULT_FOV,
+        lens: None,
+    };
+    (objects, lights, camera)
+}
+
+fn channels(pixel: u32) -> [i32; 3] {
+    [(pixel >> 16 & 0xFF) as i32, (pixel >> 8 & 0xFF) as i32, (pixel & 0xFF) as i32]
+}
+
+#[test]
+fn accumulated_frames_converge_to_the_supersampled_frame() {
+    let (objects, lights, camera) = scene();
+    let config = RenderConfig::default();
+
+    let mut accumulator = Accumulator::new(WIDTH, HEIGHT);
+    for _ in 0..SAMPLES {
+        accumulator.add_sample(&objects, &camera, &lights, &config);
+    }
+    assert_eq!(accumulator.samples(), SAMPLES);
+    let mut accumulated = vec![0u32; WIDTH * HEIGHT];
+    accumulator.resolve(&mut accumulated, &config);
+
+    // Las N muestras de N cuadros son las mismas que toma `render` con N muestras por píxel
+    let mut supersampled = vec![0u32; WIDTH * HEIGHT];
+    let supersampling = RenderConfig { samples_per_pixel: SAMPLES, ..RenderConfig::default() };
+    render(&mut supersampled, WIDTH, HEIGHT, &objects, &camera, &lights, &supersampling);
+
+    for (a, b) in accumulated.iter().zip(&supersampled) {
+        let (a, b) = (channels(*a), channels(*b));
+        assert!((0..3).all(|c| (a[c] - b[c]).abs() <= 1), "{:?} vs {:?}", a, b);
+    }
+    // Y en los bordes de la esfera quedan tonos intermedios, no solo fondo o esfera
+    let background = channels(supersampled[0]);
+    let center = channels(supersampled[HEIGHT / 2 * WIDTH + WIDTH / 2]);
+    assert!(supersampled.iter().map(|pixel| channels(*pixel)).any(|pixel| pixel != background && pixel[0] < center[0] - 10));
+}
+
+#[test]
+fn reset_discards_the_accumulated_samples() {
+    let (objects, lights, camera) = scene();
+    let config = RenderConfig::default();
+
+    let mut fresh = Accumulator::new(WIDTH, HEIGHT);
+    fresh.add_sample(&objects, &camera, &lights, &config);
+    let mut expected = vec![0u32; WIDTH * HEIGHT];
+    fresh.resolve(&mut expected, &config);
+
+    let mut accumulator = Accumulator::new(WIDTH, HEIGHT);
+    for _ in 0..3 {
+        accumulator.add_sample(&objects, &camera, &lights, &config);
+    }
+    accumulator.reset();
+    assert_eq!(accumulator.samples(), 0);
+
+    // Tras reiniciar, la primera muestra vuelve a ser la del primer cuadro
+    accumulator.add_sample(&objects, &camera, &lights, &config);
+    let mut resolved = vec![0u32; WIDTH * HEIGHT];
+    accumulator.resolve(&mut resolved, &config);
+    assert_eq!(resolved, expected);
+}
+
+#[test]
+fn first_sample_matches_a_single_sample_render_of_a_mipmapped_texture() {
+    // Damero de un texel sobre un suelo que se aleja: el nivel de mip depende del cono de cada píxel
+    let checker = (0..64 * 64)
+        .map(|i| if (i % 64 + i / 64) % 2 == 0 { Color::new(240, 240, 240) } else { Color::new(20, 20, 20) })
+        .collect();
+    let mut floor = Material::matte_white();
+    floor.texture = Some(Arc::new(Texture::new(64, 64, checker).with_mipmaps()));
+    let objects: Vec<Box<dyn RayIntersect>> =
+        vec![Box::new(Plane::new(Vec3::new(0.0, -1.0, 0.0), Vec3::new(0.0, 1.0, 0.0), floor))];
+    let (_, lights, camera) = scene();
+    let config = RenderConfig::default();
+
+    let mut accumulator = Accumulator::new(WIDTH, HEIGHT);
+    accumulator.add_sample(&objects, &camera, &lights, &config);
+    let mut accumulated = vec![0u32; WIDTH * HEIGHT];
+    accumulator.resolve(&mut accumulated, &config);
+
+    // La primera muestra no se desplaza y lleva el mismo cono que los rayos de `render`
+    let mut rendered = vec![0u32; WIDTH * HEIGHT];
+    render(&mut rendered, WIDTH, HEIGHT, &objects, &camera, &lights, &config);
+    assert_eq!(accumulated, rendered);
+}
@@ -0,0 +1,188 @@
+use std::any::Any;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+
+use nalgebra_glm::Vec3;
+use ray_tracing::aabb::Aabb;
+use ray_tracing::bvh::Bvh;
+use ray_tracing::cube::Cube;
+use ray_tracing::intersect::{Intersect, RayIntersect};
+use ray_tracing::material::Material;
+use ray_tracing::plane::Plane;
+use ray_tracing::bvh::flatten;
+use ray_tracing::camera::{Camera, DEFAULT_FOV};
+use ray_tracing::render::{pick, RenderConfig, RAY_EPSILON};
+
+// Cubo que cuenta cuántas veces se le pidió una intersección
+struct Counted {
+    cube: Cube,
+    tests: Arc<AtomicUsize>,
+}
+
+impl RayIntersect for Counted {
+    fn ray_intersect(&self, ray_origin: &Vec3, ray_direction: &Vec3, t_min: f32, t_max: f32) -> Intersect {
+        self.tests.fetch_add(1, Ordering::Relaxed);
+        self.cube.ray_intersect(ray_origin, ray_direction, t_min, t_max)
+    }
+
+    fn bounding_box(&self) -> Aabb {
+        self.cube.bounding_box()
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        self
+    }
+}
+
+// Suelo de 16x16 cubos como el de la escena de ejemplo, con algunas columnas encima
+fn sample_scene(tests: &Arc<AtomicUsize>) -> Vec<Box<dyn RayIntersect>> {
+    let mut objects: Vec<Box<dyn RayIntersect>> = Vec::new();
+    for x in 0..16 {
+        for z in 0..16 {
+            let height = if (x * 7 + z * 3) % 11 == 0 { 3 } else { 1 };
+            for y in 0..height {
+                let center = Vec3::new(x as f32 - 8.0, y as f32, -(z as f32) - 2.0);
+                objects.push(Box::new(Counted { cube: Cube::uniform(center, 1.0, Material::black()), tests: Arc::clone(tests) }));
+            }
+        }
+    }
+    objects
+}
+
+// Rayos desde una cámara arriba y adelante del suelo, en abanico sobre toda la escena
+fn rays() -> Vec<(Vec3, Vec3)> {
+    let eye = Vec3::new(0.0, 8.0, 6.0);
+    (0..32 * 32)
+        .map(|i| {
+            let target = Vec3::new((i % 32) as f32 * 0.6 - 9.6, 0.5, -((i / 32) as f32) * 0.6);
+            (eye, (target - eye).normalize())
+        })
+        .collect()
+}
+
+#[test]
+fn bvh_finds_the_same_hits_as_the_linear_scan_with_far_fewer_tests() {
+    let tests = Arc::new(AtomicUsize::new(0));
+    let linear = sample_scene(&tests);
+    let bvh = Bvh::new(sample_scene(&tests));
+    assert_eq!(bvh.len(), linear.len());
+
+    let mut linear_tests = 0;
+    let mut bvh_tests = 0;
+    for (origin, direction) in rays() {
+        tests.store(0, Ordering::Relaxed);
+        // El mismo recorrido lineal que hace `cast_ray`
+        let mut expected = Intersect::empty();
+        let mut closest_distance = f32::INFINITY;
+        for object in &linear {
+            let hit = object.ray_intersect(&origin, &direction, RAY_EPSILON, closest_distance);
+            if hit.is_intersecting {
+                closest_distance = hit.distance;
+                expected = hit;
+            }
+        }
+        linear_tests += tests.swap(0, Ordering::Relaxed);
+
+        let hit = bvh.ray_intersect(&origin, &direction, RAY_EPSILON, f32::INFINITY);
+        bvh_tests += tests.load(Ordering::Relaxed);
+
+        assert_eq!(hit.is_intersecting, expected.is_intersecting);
+        if hit.is_intersecting {
+            // En las aristas entre cubos vecinos dos caras empatan; basta con que el punto coincida
+            assert!((hit.distance - expected.distance).abs() < 1e-5);
+            assert!((hit.point - expected.point).magnitude() < 1e-4);
+        }
+        assert_eq!(bvh.intersect_any(&origin, &direction, RAY_EPSILON, f32::INFINITY).is_some(), expected.is_intersecting);
+    }
+
+    // Con casi 300 cubos, la jerarquía prueba una pequeña fracción de los objetos por rayo
+    assert!(bvh_tests * 10 < linear_tests);
+}
+
+#[test]
+fn planes_stay_outside_the_tree_but_are_still_hit() {
+    let tests = Arc::new(AtomicUsize::new(0));
+    let mut objects = sample_scene(&tests);
+    objects.push(Box::new(Plane::new(Vec3::new(0.0, -5.0, 0.0), Vec3::new(0.0, 1.0, 0.0), Material::black())));
+    let bvh = Bvh::new(objects);
+    assert!(!bvh.bounding_box().is_finite());
+
+    // Un rayo que pasa lejos de los cubos termina en el plano
+    let hit = bvh.ray_intersect(&Vec3::new(30.0, 0.0, 0.0), &Vec3::new(0.0, -1.0, 0.0), RAY_EPSILON, f32::INFINITY);
+    assert!(hit.is_intersecting);
+    assert!((hit.distance - 5.0).abs() < 1e-5);
+}
+
+#[test]
+fn pick_reports_the_same_object_through_the_tree() {
+    let tests = Arc::new(AtomicUsize::new(0));
+    let flat = sample_scene(&tests);
+    // Como en el visor: una BVH con lo fijo y un objeto suelto después de ella
+    let mut accelerated: Vec<Box<dyn RayIntersect>> = vec![Box::new(Bvh::new(sample_scene(&tests)))];
+    accelerated.push(Box::new(Cube::uniform(Vec3::new(0.0, 4.0, -6.0), 1.0, Material::black())));
+    assert_eq!(flatten(&accelerated).len(), flat.len() + 1);
+
+    let camera = Camera {
+        eye: Vec3::new(0.0, 8.0, 6.0),
+        center: Vec3::new(0.0, 0.0, -8.0),
+        up: Vec3::new(0.0, 1.0, 0.0),
+        fov: DEFAULT_FOV,
+        lens: None,
+    };
+    let config = RenderConfig::default();
+    let (width, height) = (40, 30);
+    let mut loose_cube_seen = false;
+    for y in 0..height {
+        for x in 0..width {
+            let (x, y) = (x as f32, y as f32);
+            let expected = pick(&flat, &camera, &config, x, y, width, height);
+            let picked = pick(&accelerated, &camera, &config, x, y, width, height);
+            match (expected, picked) {
+                // El cubo suelto tapa parte del suelo; su id es el siguiente a los de la BVH
+                (_, Some(picked)) if picked.object == flat.len() => loose_cube_seen = true,
+                (Some(expected), Some(picked)) => {
+                    assert_eq!(picked.object, expected.object);
+                    assert!((picked.point - expected.point).magnitude() < 1e-4);
+                }
+                (expected, picked) => assert_eq!(expected.is_some(), picked.is_some()),
+            }
+        }
+    }
+    assert!(loose_cube_seen);
+}
+
+// Suelo de 96x96 cubos pequeños con alturas variadas: bastantes objetos para que `Bvh::new` construya
+// los niveles de arriba en paralelo
+fn dense_scene(tests: &Arc<AtomicUsize>) -> Vec<Box<dyn RayIntersect>> {
+    (0..96 * 96)
+        .map(|i| {
+            let (x, z) = (i % 96, i / 96);
+            let center = Vec3::new(x as f32 * 0.2 - 9.6, ((x * 5 + z * 3) % 7) as f32 * 0.1, -(z as f32) * 0.2);
+            let cube = Cube::uniform(center, 0.15, Material::black());
+            Box::new(Counted { cube, tests: Arc::clone(tests) }) as Box<dyn RayIntersect>
+        })
+        .collect()
+}
+
+#[test]
+fn parallel_build_gives_the_same_tree_as_a_single_thread() {
+    let pool = |threads| rayon::ThreadPoolBuilder::new().num_threads(threads).build().expect("thread pool");
+    let (serial_tests, parallel_tests) = (Arc::new(AtomicUsize::new(0)), Arc::new(AtomicUsize::new(0)));
+    let serial = pool(1).install(|| Bvh::new(dense_scene(&serial_tests)));
+    let parallel = pool(4).install(|| Bvh::new(dense_scene(&parallel_tests)));
+
+    // El mismo árbol encuentra el mismo objeto probando exactamente los mismos objetos en cada rayo
+    let mut hits = 0;
+    for (origin, direction) in rays() {
+        let closest = |bvh: &Bvh| bvh.closest_hit(&origin, &direction, RAY_EPSILON, f32::INFINITY).map(|(id, hit)| (id, hit.distance));
+        let (expected, found) = (closest(&serial), closest(&parallel));
+        assert_eq!(found, expected);
+        assert_eq!(parallel_tests.swap(0, Ordering::Relaxed), serial_tests.swap(0, Ordering::Relaxed));
+        hits += usize::from(found.is_some());
+    }
+    assert!(hits > 0);
+}
@@ -0,0 +1,23 @@
+use nalgebra_glm::Vec3;
+use ray_tracing::camera::{Camera, DEFAULT_FOV};
+
+#[test]
+fn pan_on_a_tilted_camera_moves_in_the_screen_plane() {
+    // Cámara inclinada 45 grados hacia abajo, con el arriba del mundo
+    let mut camera = Camera { eye: Vec3::new(1.0, 2.0, 3.0), center: Vec3::new(1.0, 1.0, 2.0), up: Vec3::new(0.0, 1.0, 0.0), fov: DEFAULT_FOV, lens: None };
+    let view = camera.center - camera.eye;
+    let eye = camera.eye;
+
+    camera.pan(0.5, 2.0);
+    let offset = camera.eye - eye;
+
+    // Sigue mirando hacia el mismo lado, y el desplazamiento queda en el plano de la pantalla
+    assert!((camera.center - camera.eye - view).norm() < 1e-5);
+    assert!(offset.dot(&view.normalize()).abs() < 1e-5, "offset {:?}", offset);
+    assert!((offset.norm() - (0.5f32 * 0.5 + 2.0 * 2.0).sqrt()).abs() < 1e-5);
+
+    // `dy` sigue el arriba real de la pantalla, inclinado hacia atrás, no el del mundo
+    let screen_up = Vec3::new(0.0, 1.0, -1.0).normalize();
+    let right = Vec3::new(1.0, 0.0, 0.0);
+    assert!((offset - (0.5 * right + 2.0 * screen_up)).norm() < 1e-5, "offset {:?}", offset);
+}
@@ -0,0 +1,44 @@
+use ray_tracing::color::Color;
+
+#[test]
+fn non_finite_channels_become_black() {
+    let color = Color::from_f32(f32::NAN, f32::INFINITY, f32::NEG_INFINITY);
+    assert_eq!((color.r, color.g, color.b), (0, 0, 0));
+}
+
+#[test]
+fn finite_channels_are_clamped_to_the_byte_range() {
+    let color = Color::from_f32(-20.0, 128.7, 300.0);
+    assert_eq!((color.r, color.g, color.b), (0, 128, 255));
+}
+
+#[test]
+fn normalized_channels_round_trip() {
+    for value in 0..=255u8 {
+        let color = Color::new(value, 255 - value, value / 2);
+        let back = Color::from_normalized(color.rf(), color.gf(), color.bf());
+        assert_eq!((back.r, back.g, back.b), (color.r, color.g, color.b));
+    }
+}
+
+#[test]
+fn from_normalized_clamps_out_of_range_values() {
+    let color = Color::from_normalized(-0.5, 0.5, 2.0);
+    assert_eq!((color.r, color.g, color.b), (0, 128, 255));
+}
+
+#[test]
+fn white_has_unit_luminance() {
+    assert!((Color::new(255, 255, 255).luminance() - 1.0).abs() < 1e-6);
+    assert_eq!(Color::new(0, 0, 0).luminance(), 0.0);
+}
+
+#[test]
+fn grayscale_keeps_luminance_with_equal_channels() {
+    for color in [Color::new(255, 0, 0), Color::new(0, 255, 0), Color::new(0, 0, 255), Color::new(30, 140, 220)] {
+        let gray = color.grayscale();
+        assert!(gray.r == gray.g && gray.g == gray.b, "{:?}", gray);
+        // Solo cambia por redondear a un byte
+        assert!((gray.luminance() - color.luminance()).abs() <= 0.5 / 255.0 + 1e-6, "{:?} -> {:?}", color, gray);
+    }
+}
@@ -0,0 +1,104 @@
+use nalgebra_glm::Vec3;
+use ray_tracing::camera::{Camera, DEFAULT_FOV};
+use ray_tracing::denoise::denoise_atrous;
+use ray_tracing::gbuffer::GBuffer;
+use ray_tracing::intersect::RayIntersect;
+use ray_tracing::render::{render, RenderConfig};
+use ray_tracing::sampling::{hash, random_f32};
+
+const WIDTH: usize = 32;
+const HEIGHT: usize = 24;
+const BASE: [f32; 2] = [110.0, 140.0];  // Gris de cada superficie; lo bastante cercanos para que el color solo no las separe
+const NOISE: f32 = 24.0;
+
+// Dos superficies separadas en x = WIDTH / 2, cada una con su normal y su profundidad, y un framebuffer
+// con el gris de cada una más ruido determinista
+fn two_surfaces(normals: [Vec3; 2], depths: [f32; 2]) -> (Vec<u32>, GBuffer) {
+    let side = |i: usize| usize::from(i % WIDTH >= WIDTH / 2);
+    let framebuffer = (0..WIDTH * HEIGHT)
+        .map(|i| {
+            let gray = (BASE[side(i)] + (random_f32(hash(i as u32)) - 0.5) * 2.0 * NOISE).round() as u32;
+            (gray << 16) | (gray << 8) | gray
+        })
+        .collect();
+    let gbuffer = GBuffer {
+        width: WIDTH,
+        height: HEIGHT,
+        normals: (0..WIDTH * HEIGHT).map(|i| normals[side(i)]).collect(),
+        depths: (0..WIDTH * HEIGHT).map(|i| depths[side(i)]).collect(),
+    };
+    (framebuffer, gbuffer)
+}
+
+// Media y varianza del canal verde en las columnas dadas
+fn stats(framebuffer: &[u32], columns: std::ops::Range<usize>) -> (f32, f32) {
+    let values: Vec<f32> = (0..HEIGHT)
+        .flat_map(|y| columns.clone().map(move |x| y * WIDTH + x))
+        .map(|i| ((framebuffer[i] >> 8) & 0xFF) as f32)
+        .collect();
+    let mean = values.iter().sum::<f32>() / values.len() as f32;
+    let variance = values.iter().map(|v| (v - mean) * (v - mean)).sum::<f32>() / values.len() as f32;
+    (mean, variance)
+}
+
+fn assert_denoised_without_bleeding(normals: [Vec3; 2], depths: [f32; 2]) {
+    let (noisy, gbuffer) = two_surfaces(normals, depths);
+    let mut denoised = noisy.clone();
+    denoise_atrous(&mut denoised, &gbuffer, &RenderConfig { denoise_iterations: 3, ..RenderConfig::default() });
+
+    for (side, columns) in [(0, 0..WIDTH / 2), (1, WIDTH / 2..WIDTH)] {
+        let (_, noisy_variance) = stats(&noisy, columns.clone());
+        let (mean, variance) = stats(&denoised, columns);
+        assert!(variance < noisy_variance * 0.25, "variance {} -> {}", noisy_variance, variance);
+        assert!((mean - BASE[side]).abs() < 4.0, "mean {} for base {}", mean, BASE[side]);
+    }
+
+    // Las columnas pegadas al borde no toman el gris de la otra superficie
+    let (left, _) = stats(&denoised, WIDTH / 2 - 1..WIDTH / 2);
+    let (right, _) = stats(&denoised, WIDTH / 2..WIDTH / 2 + 1);
+    assert!((left - BASE[0]).abs() < 6.0 && (right - BASE[1]).abs() < 6.0, "edge columns {} and {}", left, right);
+}
+
+#[test]
+fn zero_iterations_leave_the_frame_unchanged() {
+    let (noisy, gbuffer) = two_surfaces([Vec3::new(0.0, 0.0, 1.0); 2], [5.0; 2]);
+    let mut framebuffer: Vec<u32> = noisy.iter().map(|pixel| pixel | 0x8000_0000).collect();
+    let expected = framebuffer.clone();
+    denoise_atrous(&mut framebuffer, &gbuffer, &RenderConfig { denoise_iterations: 0, ..RenderConfig::default() });
+    assert_eq!(framebuffer, expected);
+}
+
+#[test]
+fn denoise_stops_at_a_normal_edge() {
+    assert_denoised_without_bleeding([Vec3::new(0.0, 0.0, 1.0), Vec3::new(1.0, 0.0, 0.0)], [5.0, 5.0]);
+}
+
+#[test]
+fn denoise_stops_at_a_depth_edge() {
+    let normal = Vec3::new(0.0, 0.0, 1.0);
+    assert_denoised_without_bleeding([normal, normal], [5.0, 40.0]);
+}
+
+#[test]
+fn region_render_leaves_the_rest_of_the_frame_undenoised() {
+    // El cuadro anterior tiene ruido fuera de la región; el filtro lo suavizaría si corriera sobre todo
+    let (previous, _) = two_surfaces([Vec3::new(0.0, 0.0, 1.0); 2], [5.0; 2]);
+    let mut framebuffer = previous.clone();
+    let objects: Vec<Box<dyn RayIntersect>> = Vec::new();
+    let camera = Camera {
+        eye: Vec3::zeros(),
+        center: Vec3::new(0.0, 0.0, -1.0),
+        up: Vec3::new(0.0, 1.0, 0.0),
+        fov: DEFAULT_FOV,
+        lens: None,
+    };
+    let config = RenderConfig { region: Some((8, 6, 24, 18)), denoise_iterations: 3, ..RenderConfig::default() };
+    render(&mut framebuffer, WIDTH, HEIGHT, &objects, &camera, &[], &config);
+
+    for (i, (pixel, old)) in framebuffer.iter().zip(&previous).enumerate() {
+        let (x, y) = (i % WIDTH, i / WIDTH);
+        if !(8..24).contains(&x) || !(6..18).contains(&y) {
+            assert_eq!(pixel, old, "pixel ({}, {})", x, y);
+        }
+    }
+}
@@ -0,0 +1,17 @@
+use ray_tracing::export::save_ppm;
+
+#[test]
+fn ppm_has_a_p6_header_and_rgb_bytes() {
+    let path = std::env::temp_dir().join(format!("ray_tracing_ppm_{}.ppm", std::process::id()));
+    let path = path.to_string_lossy().into_owned();
+    // El byte alto (alfa) no se guarda
+    let framebuffer = [0xFF102030, 0x00A0B0C0];
+
+    save_ppm(&path, &framebuffer, 2, 1).expect("save ppm");
+    let data = std::fs::read(&path).expect("read ppm");
+    let _ = std::fs::remove_file(&path);
+
+    let header = b"P6\n2 1\n255\n";
+    assert_eq!(&data[..header.len()], header);
+    assert_eq!(&data[header.len()..], &[0x10, 0x20, 0x30, 0xA0, 0xB0, 0xC0]);
+}
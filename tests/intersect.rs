@@ -0,0 +1,204 @@
+use std::sync::Arc;
+
+use nalgebra_glm::Vec3;
+use ray_tracing::aabb::Aabb;
+use ray_tracing::cube::Cube;
+use ray_tracing::intersect::RayIntersect;
+use ray_tracing::material::Material;
+use ray_tracing::render::RAY_EPSILON;
+use ray_tracing::sphere::Sphere;
+use ray_tracing::transform::{Instance, Transform, Transformed};
+use ray_tracing::triangle::Triangle;
+
+fn unit_cube() -> Cube {
+    Cube::uniform(Vec3::zeros(), 1.0, Material::black())
+}
+
+#[test]
+fn front_face_reports_the_side_the_ray_came_from() {
+    let cube = unit_cube();
+    let direction = Vec3::new(0.0, 0.0, -1.0);
+
+    // Desde afuera, el rayo entra por la cara +Z
+    let outside = cube.ray_intersect(&Vec3::new(0.0, 0.0, 3.0), &direction, RAY_EPSILON, f32::INFINITY);
+    assert!(outside.is_intersecting);
+    assert!(outside.front_face);
+    assert_eq!(outside.normal, Vec3::new(0.0, 0.0, 1.0));
+
+    // Desde adentro, el rayo sale por la cara -Z; la normal sigue apuntando hacia afuera
+    let inside = cube.ray_intersect(&Vec3::zeros(), &direction, RAY_EPSILON, f32::INFINITY);
+    assert!(inside.is_intersecting);
+    assert!(!inside.front_face);
+    assert_eq!(inside.normal, Vec3::new(0.0, 0.0, -1.0));
+}
+
+#[test]
+fn sphere_is_hit_from_outside_and_inside() {
+    let sphere = Sphere::new(Vec3::new(0.0, 0.0, -5.0), 1.0, Material::black());
+    let direction = Vec3::new(0.0, 0.0, -1.0);
+
+    let outside = sphere.ray_intersect(&Vec3::zeros(), &direction, RAY_EPSILON, f32::INFINITY);
+    assert!(outside.is_intersecting);
+    assert!(outside.front_face);
+    assert!((outside.distance - 4.0).abs() < 1e-4);
+    assert!((outside.normal - Vec3::new(0.0, 0.0, 1.0)).magnitude() < 1e-4);
+
+    // Un rayo que sale de la superficie hacia adentro no vuelve a chocar en su propio origen
+    let inside = sphere.ray_intersect(&outside.point, &direction, RAY_EPSILON, f32::INFINITY);
+    assert!(inside.is_intersecting);
+    assert!(!inside.front_face);
+    assert!((inside.distance - 2.0).abs() < 1e-4);
+
+    // Apuntando hacia otro lado no hay impacto
+    let miss = sphere.ray_intersect(&Vec3::zeros(), &Vec3::new(0.0, 1.0, 0.0), RAY_EPSILON, f32::INFINITY);
+    assert!(!miss.is_intersecting);
+}
+
+#[test]
+fn moved_cube_hits_like_a_cube_built_there() {
+    let target = Vec3::new(1.5, -0.25, -3.0);
+    let mut moved = unit_cube();
+    moved.set_center(target);
+    let built = Cube::uniform(target, 1.0, Material::black());
+
+    // Rayos desde varios orígenes hacia distintos puntos del cubo, incluido uno que lo roza y uno que falla
+    let origins = [Vec3::zeros(), Vec3::new(4.0, 2.0, 1.0), Vec3::new(-3.0, -1.0, -6.0)];
+    let targets = [target, target + Vec3::new(0.3, 0.4, -0.2), target + Vec3::new(0.5, 0.5, 0.5), target + Vec3::new(2.0, 0.0, 0.0)];
+    for origin in origins {
+        for point in targets {
+            let direction = (point - origin).normalize();
+            let a = moved.ray_intersect(&origin, &direction, RAY_EPSILON, f32::INFINITY);
+            let b = built.ray_intersect(&origin, &direction, RAY_EPSILON, f32::INFINITY);
+            assert_eq!(a.is_intersecting, b.is_intersecting);
+            assert_eq!(a.distance, b.distance);
+            assert_eq!(a.normal, b.normal);
+            assert_eq!((a.u, a.v, a.front_face), (b.u, b.v, b.front_face));
+        }
+    }
+    assert_eq!(moved.bounding_box().min, built.bounding_box().min);
+}
+
+#[test]
+fn transformed_sphere_hits_like_a_sphere_built_in_place() {
+    let center = Vec3::new(1.0, 0.5, -4.0);
+    // Rotar una esfera centrada en el origen no cambia su forma; después se traslada a `center`
+    let transformed = Transformed::new(
+        Sphere::new(Vec3::zeros(), 1.5, Material::black()),
+        Transform::new(center, Vec3::new(30.0, 45.0, 10.0), Vec3::repeat(1.0)),
+    );
+    let built = Sphere::new(center, 1.5, Material::black());
+
+    let origins = [Vec3::zeros(), Vec3::new(3.0, 2.0, 1.0), center];
+    let targets = [center, center + Vec3::new(0.9, 0.6, 0.3), center + Vec3::new(0.0, 1.4, 0.0), center + Vec3::new(3.0, 0.0, 0.0)];
+    for origin in origins {
+        for point in targets {
+            let direction = (point - origin).normalize();
+            let a = transformed.ray_intersect(&origin, &direction, RAY_EPSILON, f32::INFINITY);
+            let b = built.ray_intersect(&origin, &direction, RAY_EPSILON, f32::INFINITY);
+            assert_eq!(a.is_intersecting, b.is_intersecting);
+            if a.is_intersecting {
+                assert!((a.distance - b.distance).abs() < 1e-4);
+                assert!((a.point - b.point).magnitude() < 1e-4);
+                assert!((a.normal - b.normal).magnitude() < 1e-4);
+                assert_eq!(a.front_face, b.front_face);
+            }
+        }
+    }
+
+    // La caja de la caja local rotada es más holgada, pero siempre contiene a la esfera
+    let (a, b) = (transformed.bounding_box(), built.bounding_box());
+    assert!((0..3).all(|i| a.min[i] <= b.min[i] + 1e-4 && a.max[i] >= b.max[i] - 1e-4));
+}
+
+#[test]
+fn instances_share_one_sphere_at_different_positions() {
+    let sphere: Arc<dyn RayIntersect> = Arc::new(Sphere::new(Vec3::zeros(), 1.0, Material::black()));
+    let left = Vec3::new(-3.0, 0.0, -5.0);
+    let right = Vec3::new(3.0, 1.0, -5.0);
+    let instances: Vec<Instance> = [left, right]
+        .into_iter()
+        .map(|position| Transformed::new(Arc::clone(&sphere), Transform::new(position, Vec3::zeros(), Vec3::repeat(1.0))))
+        .collect();
+    assert_eq!(Arc::strong_count(&sphere), 3);
+
+    // Cada instancia se ve donde fue colocada y solo ahí
+    for (instance, center) in instances.iter().zip([left, right]) {
+        let direction = center.normalize();
+        let hit = instance.ray_intersect(&Vec3::zeros(), &direction, RAY_EPSILON, f32::INFINITY);
+        assert!(hit.is_intersecting);
+        assert!((hit.distance - (center.magnitude() - 1.0)).abs() < 1e-4);
+        assert!((hit.normal + direction).magnitude() < 1e-4);
+    }
+    let between = Vec3::new(0.0, 0.0, -1.0);
+    assert!(instances.iter().all(|instance| !instance.ray_intersect(&Vec3::zeros(), &between, RAY_EPSILON, f32::INFINITY).is_intersecting));
+}
+
+#[test]
+fn triangle_interpolates_normals_and_uvs() {
+    let mut triangle = Triangle::new(
+        [Vec3::new(0.0, 0.0, -2.0), Vec3::new(2.0, 0.0, -2.0), Vec3::new(0.0, 2.0, -2.0)],
+        Material::black(),
+    );
+    let direction = Vec3::new(0.0, 0.0, -1.0);
+
+    // Sin normales por vértice se usa la de la cara, que mira hacia la cámara por el orden antihorario
+    let hit = triangle.ray_intersect(&Vec3::new(0.5, 0.5, 0.0), &direction, RAY_EPSILON, f32::INFINITY);
+    assert!(hit.is_intersecting && hit.front_face);
+    assert!((hit.distance - 2.0).abs() < 1e-5);
+    assert!((hit.normal - Vec3::new(0.0, 0.0, 1.0)).magnitude() < 1e-5);
+
+    // Fuera de la hipotenusa no hay cruce
+    assert!(!triangle.ray_intersect(&Vec3::new(1.5, 1.5, 0.0), &direction, RAY_EPSILON, f32::INFINITY).is_intersecting);
+
+    // En el punto medio de la hipotenusa, normal y UV son el promedio de los dos vértices de ese lado
+    let tilted = Vec3::new(1.0, 0.0, 1.0).normalize();
+    triangle.normals = Some([Vec3::new(0.0, 0.0, 1.0), tilted, tilted]);
+    triangle.uvs = Some([(0.0, 0.0), (1.0, 0.0), (0.0, 1.0)]);
+    let hit = triangle.ray_intersect(&Vec3::new(1.0, 1.0, 0.0), &direction, RAY_EPSILON, f32::INFINITY);
+    assert!((hit.normal - tilted).magnitude() < 1e-4);
+    assert!((hit.u - 0.5).abs() < 1e-4 && (hit.v - 0.5).abs() < 1e-4);
+    assert!((hit.uv_scale - 0.5).abs() < 1e-4);
+}
+
+#[test]
+fn aabb_slab_test_and_union() {
+    let a = Aabb::new(Vec3::new(-1.0, -1.0, -1.0), Vec3::new(1.0, 1.0, 1.0));
+    let b = Aabb::new(Vec3::new(0.0, 2.0, -3.0), Vec3::new(4.0, 3.0, 0.0));
+    let union = a.union(&b);
+    assert_eq!(union.min, Vec3::new(-1.0, -1.0, -3.0));
+    assert_eq!(union.max, Vec3::new(4.0, 3.0, 1.0));
+
+    let inv_dir = |d: Vec3| Vec3::new(1.0 / d.x, 1.0 / d.y, 1.0 / d.z);
+
+    // Entra por la cara +Z; desde adentro la entrada es el propio origen; hacia atrás no la cruza
+    let forward = inv_dir(Vec3::new(0.0, 0.0, -1.0));
+    assert_eq!(a.intersects_ray(&Vec3::new(0.0, 0.0, 5.0), &forward), Some(4.0));
+    assert_eq!(a.intersects_ray(&Vec3::zeros(), &forward), Some(0.0));
+    assert_eq!(a.intersects_ray(&Vec3::new(0.0, 0.0, -5.0), &forward), None);
+
+    // Un rayo paralelo a una cara pero por fuera de la caja no la toca
+    assert_eq!(a.intersects_ray(&Vec3::new(0.0, 2.0, 5.0), &forward), None);
+
+    // Mismo resultado que el cubo que ocupa la caja
+    let direction = Vec3::new(0.05, -0.02, -1.0).normalize();
+    let origin = Vec3::new(-0.2, 0.1, 4.0);
+    let hit = unit_cube().ray_intersect(&origin, &direction, RAY_EPSILON, f32::INFINITY);
+    let entry = unit_cube().bounding_box().intersects_ray(&origin, &inv_dir(direction)).expect("ray hits the box");
+    assert!((hit.distance - entry).abs() < 1e-5);
+}
+
+#[test]
+fn resizing_or_moving_a_cube_updates_its_hits() {
+    let mut cube = unit_cube();
+    let origin = Vec3::new(0.0, 0.0, 5.0);
+    let direction = Vec3::new(0.0, 0.0, -1.0);
+
+    cube.set_size(4.0);
+    assert_eq!(cube.size(), 4.0);
+    assert!((cube.ray_intersect(&origin, &direction, RAY_EPSILON, f32::INFINITY).distance - 3.0).abs() < 1e-5);
+
+    cube.set_center(Vec3::new(0.0, 0.0, -1.0));
+    assert_eq!(cube.center(), Vec3::new(0.0, 0.0, -1.0));
+    assert!((cube.ray_intersect(&origin, &direction, RAY_EPSILON, f32::INFINITY).distance - 4.0).abs() < 1e-5);
+    assert_eq!(cube.bounding_box().max, Vec3::new(2.0, 2.0, 1.0));
+}
@@ -0,0 +1,42 @@
+use std::sync::Arc;
+
+use nalgebra_glm::Vec3;
+use ray_tracing::color::Color;
+use ray_tracing::material::Material;
+use ray_tracing::render::specular_power;
+use ray_tracing::texture::Texture;
+
+#[test]
+fn extreme_specular_values_give_a_finite_highlight() {
+    for specular in [0.0, f32::NAN, 1e30, -5.0, f32::INFINITY, f32::NEG_INFINITY] {
+        let material = Material { specular, ..Material::matte_white() };
+        let exponent = material.specular_exponent();
+        assert!((1.0..=1024.0).contains(&exponent), "specular {}: exponent {}", specular, exponent);
+
+        // El brillo queda en [0, 1] para cualquier ángulo, incluidos los que caen fuera de [-1, 1]
+        for cos_angle in [-1.5, -1.0, 0.0, 0.3, 0.999, 1.0, 1.5] {
+            let intensity = specular_power(cos_angle, exponent);
+            assert!((0.0..=1.0).contains(&intensity), "specular {}, cos {}: intensity {}", specular, cos_angle, intensity);
+        }
+    }
+}
+
+#[test]
+fn flat_and_textured_materials_share_the_sampling_path() {
+    let rgb = |color: Color| (color.r, color.g, color.b);
+    let texel = Color::new(30, 140, 220);
+
+    // El de color plano muestrea la textura blanca compartida y devuelve su `diffuse` sin cambios
+    let flat = Material { diffuse: Color::new(200, 100, 50), ..Material::black() };
+    assert_eq!(rgb(flat.get_diffuse_color(0.3, 0.7, &Vec3::zeros(), 0.0)), (200, 100, 50));
+
+    // Con `diffuse` blanco la imagen se ve tal cual, y otro `diffuse` la tiñe
+    let textured = Material {
+        diffuse: Color::new(255, 255, 255),
+        texture: Some(Arc::new(Texture::from_color(texel))),
+        ..Material::black()
+    };
+    assert_eq!(rgb(textured.get_diffuse_color(0.3, 0.7, &Vec3::zeros(), 0.0)), rgb(texel));
+    let tinted = Material { diffuse: Color::new(255, 0, 128), ..textured };
+    assert_eq!(rgb(tinted.get_diffuse_color(0.3, 0.7, &Vec3::zeros(), 0.0)), (30, 0, 110));
+}
@@ -0,0 +1,55 @@
+use nalgebra_glm::Vec3;
+use ray_tracing::material::Material;
+use ray_tracing::mesh::{load_obj, MeshError};
+use ray_tracing::render::RAY_EPSILON;
+
+// Escribe `contents` en un archivo OBJ temporal con nombre propio de la prueba y devuelve su ruta
+fn temp_obj(name: &str, contents: &str) -> String {
+    let path = std::env::temp_dir().join(format!("ray_tracing_{}_{}.obj", name, std::process::id()));
+    std::fs::write(&path, contents).expect("write temp mesh");
+    path.to_string_lossy().into_owned()
+}
+
+#[test]
+fn quad_is_split_into_two_textured_triangles() {
+    let path = temp_obj(
+        "quad",
+        "# cuadrado de 2x2 en z = -3\n\
+         v -1 -1 -3\nv 1 -1 -3\nv 1 1 -3\nv -1 1 -3\n\
+         vt 0 0\nvt 1 0\nvt 1 1\nvt 0 1\n\
+         vn 0 0 1\n\
+         f 1/1/1 2/2/1 3/3/1 -1/-1/-1\n",
+    );
+
+    let triangles = load_obj(&path, Material::black()).expect("load mesh");
+    let _ = std::fs::remove_file(&path);
+    assert_eq!(triangles.len(), 2);
+
+    // Cada esquina del cuadrado cae en uno de los dos triángulos, con la UV de esa esquina
+    let direction = Vec3::new(0.0, 0.0, -1.0);
+    for (x, y) in [(-0.9, -0.7), (0.9, -0.9), (0.7, 0.9), (-0.9, 0.9)] {
+        let hits: Vec<_> = triangles
+            .iter()
+            .map(|triangle| triangle.ray_intersect(&Vec3::new(x, y, 0.0), &direction, RAY_EPSILON, f32::INFINITY))
+            .filter(|hit| hit.is_intersecting)
+            .collect();
+        assert_eq!(hits.len(), 1, "corner ({}, {})", x, y);
+        assert!((hits[0].u - (x + 1.0) / 2.0).abs() < 1e-4 && (hits[0].v - (y + 1.0) / 2.0).abs() < 1e-4);
+        assert!((hits[0].normal - Vec3::new(0.0, 0.0, 1.0)).magnitude() < 1e-5);
+    }
+}
+
+#[test]
+fn out_of_range_index_is_an_error() {
+    let path = temp_obj("bad_index", "v 0 0 0\nv 1 0 0\nv 0 1 0\nf 1 2 4\n");
+
+    let result = load_obj(&path, Material::black());
+    let _ = std::fs::remove_file(&path);
+    match result {
+        Err(MeshError::BadIndex { line, index, .. }) => {
+            assert_eq!(line, 4);
+            assert_eq!(index, "4");
+        }
+        other => panic!("expected BadIndex, got {:?}", other.map(|triangles| triangles.len())),
+    }
+}
@@ -0,0 +1,544 @@
+use std::sync::Arc;
+
+use nalgebra_glm::Vec3;
+use ray_tracing::aov::Aovs;
+use ray_tracing::background::Background;
+use ray_tracing::camera::{Camera, Lens, DEFAULT_FOV};
+use ray_tracing::color::Color;
+use ray_tracing::cube::Cube;
+use ray_tracing::intersect::{Intersect, RayIntersect};
+use ray_tracing::light::{Light, LightShape};
+use ray_tracing::material::Material;
+use ray_tracing::plane::Plane;
+use ray_tracing::sphere::Sphere;
+use ray_tracing::render::{cast_ray, cast_ray_breakdown, cast_shadow, render, RenderConfig, Transparency};
+use ray_tracing::texture::Texture;
+use ray_tracing::triangle::Triangle;
+
+const WIDTH: usize = 48;
+const HEIGHT: usize = 36;
+// Diferencia máxima permitida por canal, para absorber pequeñas variaciones de punto flotante
+const TOLERANCE: u8 = 2;
+const REFERENCE: &str = "tests/reference/small_scene.png";
+
+fn flat_material(diffuse: Color, albedo: [f32; 4]) -> Material {
+    Material {
+        diffuse,
+        specular: 20.0,
+        albedo,
+        refractive_index: 1.5,
+        texture: None,
+        emission_texture: None,
+        blend: None,
+        reflection_color: Color::new(255, 255, 255),
+        noise: None,
+        thin_film: None,
+        anisotropy: None,
+        detail: None,
+        refraction_roughness: 0.0,
+        name: None,
+    }
+}
+
+// Plano en z mirando a +z que deja pasar la mitad de la luz, sin color propio ni desvío (índice 1)
+fn half_transparent_plane(z: f32) -> Box<dyn RayIntersect> {
+    let mut material = flat_material(Color::new(0, 0, 0), [0.0, 0.0, 0.0, 0.5]);
+    material.refractive_index = 1.0;
+    Box::new(Plane::new(Vec3::new(0.0, 0.0, z), Vec3::new(0.0, 0.0, 1.0), material))
+}
+
+fn cube(center: Vec3, size: f32, material: Material) -> Box<dyn RayIntersect> {
+    Box::new(Cube::uniform(center, size, material))
+}
+
+// Escena pequeña y determinista: un piso, un cubo texturizado, un cubo de vidrio y dos luces
+fn small_scene() -> (Vec<Box<dyn RayIntersect>>, Vec<Light>, Camera) {
+    let checker = Texture::new(
+        2,
+        2,
+        vec![
+            Color::new(230, 60, 40),
+            Color::new(240, 240, 240),
+            Color::new(240, 240, 240),
+            Color::new(230, 60, 40),
+        ],
+    );
+    let mut textured = flat_material(Color::new(255, 255, 255), [0.8, 0.2, 0.0, 0.0]);
+    textured.texture = Some(Arc::new(checker));
+
+    let floor = flat_material(Color::new(90, 160, 80), [0.9, 0.1, 0.0, 0.0]);
+    let glass = flat_material(Color::new(120, 160, 255), [0.5, 0.3, 0.2, 0.3]);
+
+    let objects = vec![
+        cube(Vec3::new(0.0, -3.0, 0.0), 4.0, floor),
+        cube(Vec3::new(-0.6, -0.5, 0.0), 1.0, textured),
+        cube(Vec3::new(0.9, -0.6, 0.6), 0.8, glass),
+    ];
+
+    let lights = vec![
+        Light::new(Vec3::new(0.0, 0.0, 0.0), Color::new(255, 255, 255), 0.2),
+        Light::new(Vec3::new(2.0, 5.0, 3.0), Color::new(255, 255, 255), 1.2),
+    ];
+
+    let camera = Camera {
+        eye: Vec3::new(1.6, 1.0, 2.6),
+        center: Vec3::new(0.0, -0.5, 0.0),
+        up: Vec3::new(0.0, 1.0, 0.0),
+        fov: DEFAULT_FOV,
+        lens: None,
+    };
+
+    (objects, lights, camera)
+}
+
+fn render_small_scene() -> Vec<u8> {
+    let (objects, lights, camera) = small_scene();
+    let mut framebuffer = vec![0u32; WIDTH * HEIGHT];
+    render(&mut framebuffer, WIDTH, HEIGHT, &objects, &camera, &lights, &RenderConfig::default());
+
+    framebuffer
+        .iter()
+        .flat_map(|pixel| [(pixel >> 16) as u8, (pixel >> 8) as u8, *pixel as u8])
+        .collect()
+}
+
+#[test]
+fn small_scene_matches_reference() {
+    let rendered = render_small_scene();
+    let path = format!("{}/{}", env!("CARGO_MANIFEST_DIR"), REFERENCE);
+
+    // `UPDATE_REFERENCE=1 cargo test` regenera la imagen de referencia tras un cambio visual intencional
+    if std::env::var_os("UPDATE_REFERENCE").is_some() {
+        image::save_buffer(&path, &rendered, WIDTH as u32, HEIGHT as u32, image::ColorType::Rgb8)
+            .expect("Failed to write reference image");
+        return;
+    }
+
+    let reference = image::open(&path)
+        .unwrap_or_else(|e| panic!("Failed to load {} ({}); run with UPDATE_REFERENCE=1 to create it", path, e))
+        .to_rgb8();
+    assert_eq!(
+        (reference.width() as usize, reference.height() as usize),
+        (WIDTH, HEIGHT),
+        "reference image has the wrong size"
+    );
+
+    let mismatches: Vec<usize> = rendered
+        .iter()
+        .zip(reference.as_raw())
+        .enumerate()
+        .filter(|(_, (a, b))| a.abs_diff(**b) > TOLERANCE)
+        .map(|(i, _)| i / 3)
+        .collect();
+
+    if let Some(&first) = mismatches.first() {
+        panic!(
+            "{} channel(s) differ from the reference; first at pixel ({}, {})",
+            mismatches.len(),
+            first % WIDTH,
+            first / WIDTH
+        );
+    }
+}
+
+#[test]
+fn render_is_deterministic() {
+    assert_eq!(render_small_scene(), render_small_scene());
+}
+
+// Renderiza un cubo iluminado visto desde una cámara cuyo ojo está exactamente sobre la cara +Z de
+// otro cubo, mirando hacia afuera; con `with_occluder` en false ese otro cubo no existe
+fn render_from_cube_face(with_occluder: bool) -> Vec<u32> {
+    let mut objects = vec![cube(
+        Vec3::new(0.0, 0.0, 4.0),
+        1.0,
+        flat_material(Color::new(200, 120, 60), [0.9, 0.1, 0.0, 0.0]),
+    )];
+    if with_occluder {
+        objects.push(cube(
+            Vec3::new(0.0, 0.0, 0.0),
+            1.0,
+            flat_material(Color::new(255, 255, 255), [0.9, 0.1, 0.0, 0.0]),
+        ));
+    }
+
+    let lights = vec![Light::new(Vec3::new(0.0, 3.0, 6.0), Color::new(255, 255, 255), 1.0)];
+    let camera = Camera {
+        eye: Vec3::new(0.0, 0.0, 0.5),
+        center: Vec3::new(0.0, 0.0, 4.0),
+        up: Vec3::new(0.0, 1.0, 0.0),
+        fov: DEFAULT_FOV,
+        lens: None,
+    };
+
+    let mut framebuffer = vec![0u32; WIDTH * HEIGHT];
+    render(&mut framebuffer, WIDTH, HEIGHT, &objects, &camera, &lights, &RenderConfig::default());
+    framebuffer
+}
+
+#[test]
+fn eye_on_surface_does_not_self_intersect() {
+    // El cubo sobre el que está el ojo queda detrás de la cámara, así que no debe verse nada de él
+    assert_eq!(render_from_cube_face(true), render_from_cube_face(false));
+}
+
+#[test]
+fn subtractive_light_dims_the_scene() {
+    let (objects, mut lights, camera) = small_scene();
+    let mut lit = vec![0u32; WIDTH * HEIGHT];
+    render(&mut lit, WIDTH, HEIGHT, &objects, &camera, &lights, &RenderConfig::default());
+
+    // Una luz sustractiva sobre la misma zona que ilumina la luz principal
+    lights.push(Light::subtractive(Vec3::new(2.0, 5.0, 3.0), Color::new(255, 255, 255), 0.8));
+    let mut dimmed = vec![0u32; WIDTH * HEIGHT];
+    render(&mut dimmed, WIDTH, HEIGHT, &objects, &camera, &lights, &RenderConfig::default());
+
+    let brightness = |framebuffer: &[u32]| -> u64 {
+        framebuffer
+            .iter()
+            .map(|pixel| ((pixel >> 16) & 0xFF) as u64 + ((pixel >> 8) & 0xFF) as u64 + (pixel & 0xFF) as u64)
+            .sum()
+    };
+    assert!(brightness(&dimmed) < brightness(&lit));
+}
+
+// Cuenta los rayos al renderizar un piso visto desde arriba con una sola luz en `light_position`
+fn rays_for_floor_lit_from(light_position: Vec3) -> u64 {
+    let objects = vec![cube(
+        Vec3::new(0.0, -10.0, 0.0),
+        20.0,
+        flat_material(Color::new(200, 200, 200), [0.9, 0.1, 0.0, 0.0]),
+    )];
+    let lights = vec![Light::new(light_position, Color::new(255, 255, 255), 1.0)];
+    let camera = Camera {
+        eye: Vec3::new(0.0, 1.0, 0.0),
+        center: Vec3::new(0.0, -1.0, 0.0),
+        up: Vec3::new(0.0, 0.0, -1.0),
+        fov: DEFAULT_FOV,
+        lens: None,
+    };
+
+    let mut framebuffer = vec![0u32; WIDTH * HEIGHT];
+    render(&mut framebuffer, WIDTH, HEIGHT, &objects, &camera, &lights, &RenderConfig::default()).rays
+}
+
+#[test]
+fn back_facing_surfaces_skip_shadow_rays() {
+    let primary_rays = (WIDTH * HEIGHT) as u64;
+
+    // Con la luz arriba cada píxel lanza además su rayo de sombra
+    assert_eq!(rays_for_floor_lit_from(Vec3::new(0.0, 5.0, 0.0)), 2 * primary_rays);
+    // Con la luz debajo la cara de arriba le da la espalda y no se lanza ninguno
+    assert_eq!(rays_for_floor_lit_from(Vec3::new(0.0, -5.0, 0.0)), primary_rays);
+}
+
+// Sombras a lo largo del eje x sobre el suelo (y = 0) de un techo a media altura que cubre x < 0, con
+// una luz de área de radio 1 de la forma dada justo encima del borde del techo
+fn shadow_profile(shape: LightShape) -> Vec<f32> {
+    let ceiling = Triangle::new(
+        [Vec3::new(0.0, 3.0, -50.0), Vec3::new(0.0, 3.0, 50.0), Vec3::new(-50.0, 3.0, 0.0)],
+        flat_material(Color::new(200, 200, 200), [0.9, 0.1, 0.0, 0.0]),
+    );
+    let objects: Vec<Box<dyn RayIntersect>> = vec![Box::new(ceiling)];
+    let light = Light { radius: 1.0, shape, ..Light::new(Vec3::new(0.0, 6.0, 0.0), Color::new(255, 255, 255), 1.0) };
+
+    (-40..=40)
+        .map(|i| {
+            let point = Vec3::new(i as f32 * 0.05, 0.0, 0.0);
+            let floor = Intersect::new(point, Vec3::new(0.0, 1.0, 0.0), 1.0, Material::black(), 0.0, 0.0, true);
+            cast_shadow(&floor, &light, &objects)
+        })
+        .collect()
+}
+
+// Muestras en la penumbra: ni libres de sombra ni tan oscuras como la sombra completa
+fn penumbra_width(profile: &[f32]) -> usize {
+    let umbra = profile.iter().cloned().fold(0.0, f32::max);
+    profile.iter().filter(|shadow| **shadow > 0.01 && **shadow < umbra - 0.01).count()
+}
+
+#[test]
+fn edge_on_disk_light_casts_a_narrower_penumbra_than_a_sphere() {
+    let sphere = shadow_profile(LightShape::Sphere);
+    // Con la normal en x el panel se ve de canto desde abajo: no tiene extensión a lo ancho del borde
+    let disk = shadow_profile(LightShape::Disk { normal: Vec3::new(1.0, 0.0, 0.0) });
+    assert!(penumbra_width(&sphere) > 0, "{:?}", sphere);
+    assert!(penumbra_width(&disk) < penumbra_width(&sphere), "disk {:?} sphere {:?}", disk, sphere);
+
+    // Un disco sin normal se comporta como la esfera, en vez de llevar los rayos de sombra a NaN y
+    // perder la sombra entera
+    assert_eq!(shadow_profile(LightShape::Disk { normal: Vec3::zeros() }), sphere);
+}
+
+#[test]
+fn stacked_transparent_surfaces_composite_in_order() {
+    let objects = vec![half_transparent_plane(-2.0), half_transparent_plane(-4.0)];
+    let config = RenderConfig { background: Background::Solid(Color::new(200, 100, 40)), ..RenderConfig::default() };
+
+    let color = cast_ray(&Vec3::zeros(), &Vec3::new(0.0, 0.0, -1.0), &objects, &[], &config, 0);
+
+    // Cada plano deja pasar la mitad de lo que hay detrás: un cuarto del fondo
+    for (channel, expected) in [(color.r, 50), (color.g, 25), (color.b, 10)] {
+        assert!(channel.abs_diff(expected) <= 1, "got {:?}", color);
+    }
+}
+
+#[test]
+fn max_depth_lets_rays_through_more_layers() {
+    let objects = vec![half_transparent_plane(-2.0), half_transparent_plane(-3.0), half_transparent_plane(-4.0)];
+    let shade = |max_depth: u32| {
+        let config = RenderConfig { background: Background::Solid(Color::new(240, 160, 80)), max_depth, ..RenderConfig::default() };
+        cast_ray(&Vec3::zeros(), &Vec3::new(0.0, 0.0, -1.0), &objects, &[], &config, 0)
+    };
+
+    // Con el límite por defecto (2) el rayo cruza dos capas y el segundo refractado ve el fondo sin
+    // llegar a la tercera; con 4 las atraviesa todas
+    for (max_depth, expected) in [(2, [60, 40, 20]), (4, [30, 20, 10])] {
+        let color = shade(max_depth);
+        for (channel, expected) in [color.r, color.g, color.b].into_iter().zip(expected) {
+            assert!(channel.abs_diff(expected) <= 1, "max_depth {}: got {:?}", max_depth, color);
+        }
+    }
+}
+
+// Cuenta los rayos al renderizar el espacio entre dos espejos enfrentados, que reflejan la mitad de la
+// luz cada uno: sin corte por aporte, cada píxel rebota hasta agotar `max_depth`
+fn rays_between_mirrors(min_contribution: f32) -> u64 {
+    let mirror = flat_material(Color::new(255, 255, 255), [0.5, 0.0, 0.5, 0.0]);
+    let objects: Vec<Box<dyn RayIntersect>> = vec![
+        Box::new(Plane::new(Vec3::new(0.0, 0.0, -1.0), Vec3::new(0.0, 0.0, 1.0), mirror.clone())),
+        Box::new(Plane::new(Vec3::new(0.0, 0.0, 1.0), Vec3::new(0.0, 0.0, -1.0), mirror)),
+    ];
+    let camera = Camera { eye: Vec3::zeros(), center: Vec3::new(0.0, 0.0, -1.0), up: Vec3::new(0.0, 1.0, 0.0), fov: DEFAULT_FOV, lens: None };
+    let config = RenderConfig { max_depth: 20, min_contribution, ..RenderConfig::default() };
+
+    let mut framebuffer = vec![0u32; WIDTH * HEIGHT];
+    render(&mut framebuffer, WIDTH, HEIGHT, &objects, &camera, &[], &config).rays
+}
+
+#[test]
+fn min_contribution_stops_the_chain_between_mirrors() {
+    let pixels = (WIDTH * HEIGHT) as u64;
+
+    // Sin umbral: el rayo de cámara, 19 reflejos y el que llega a max_depth y ve el fondo
+    assert_eq!(rays_between_mirrors(0.0), 21 * pixels);
+    // Con 0.1 se lanzan los reflejos que aportan 0.5, 0.25 y 0.125 del píxel; el de 0.0625 ya no
+    assert_eq!(rays_between_mirrors(0.1), 4 * pixels);
+}
+
+#[test]
+fn depth_peeling_composites_three_layers_in_any_order() {
+    let config = RenderConfig {
+        background: Background::Solid(Color::new(240, 160, 80)),
+        transparency: Transparency::DepthPeeling,
+        ..RenderConfig::default()
+    };
+
+    // Las mismas tres capas, listadas de adelante hacia atrás y en desorden
+    for order in [[-2.0, -3.0, -4.0], [-4.0, -2.0, -3.0]] {
+        let objects: Vec<Box<dyn RayIntersect>> = order.iter().map(|z| half_transparent_plane(*z)).collect();
+        let color = cast_ray(&Vec3::zeros(), &Vec3::new(0.0, 0.0, -1.0), &objects, &[], &config, 0);
+
+        // Cada capa deja pasar la mitad: un octavo del fondo
+        for (channel, expected) in [(color.r, 30), (color.g, 20), (color.b, 10)] {
+            assert!(channel.abs_diff(expected) <= 1, "order {:?}: got {:?}", order, color);
+        }
+    }
+}
+
+// Ancho y alto en píxeles de la región no negra del framebuffer
+fn lit_extent(framebuffer: &[u32], width: usize) -> (usize, usize) {
+    let lit: Vec<(usize, usize)> = framebuffer
+        .iter()
+        .enumerate()
+        .filter(|(_, pixel)| **pixel & 0xFFFFFF != 0)
+        .map(|(i, _)| (i % width, i / width))
+        .collect();
+    let span = |values: Vec<usize>| values.iter().max().unwrap() - values.iter().min().unwrap() + 1;
+    (span(lit.iter().map(|p| p.0).collect()), span(lit.iter().map(|p| p.1).collect()))
+}
+
+#[test]
+fn shading_breakdown_adds_up_to_the_color() {
+    let (objects, lights, camera) = small_scene();
+    let config = RenderConfig::default();
+    let aovs = Aovs::render(WIDTH, HEIGHT, &objects, &camera, &lights, &config);
+    let mut framebuffer = vec![0u32; WIDTH * HEIGHT];
+    render(&mut framebuffer, WIDTH, HEIGHT, &objects, &camera, &lights, &config);
+
+    for (index, pixel) in aovs.pixels.iter().enumerate() {
+        // El color completo es el mismo que produce el render normal (que al postprocesar puede truncar
+        // una unidad)
+        let channels = |color: Color| [color.r, color.g, color.b].map(|c| c as i32);
+        let rendered = [framebuffer[index] >> 16, framebuffer[index] >> 8, framebuffer[index]].map(|c| (c & 0xFF) as i32);
+        for (channel, expected) in channels(pixel.color).into_iter().zip(rendered) {
+            assert!((channel - expected).abs() <= 1, "pixel {}: {:?}", index, pixel);
+        }
+
+        // Las partes suman el color, salvo por el truncado de cada una a entero
+        let parts = [pixel.background, pixel.ambient, pixel.diffuse, pixel.specular, pixel.reflection, pixel.refraction, pixel.emission];
+        let sum = parts.iter().fold([0; 3], |sum, part| {
+            let part = channels(*part);
+            [sum[0] + part[0], sum[1] + part[1], sum[2] + part[2]]
+        });
+        for (total, expected) in sum.into_iter().zip(channels(pixel.color)) {
+            assert!((total.min(255) - expected).abs() <= parts.len() as i32, "pixel {}: {:?}", index, pixel);
+        }
+    }
+}
+
+#[test]
+fn red_light_on_a_white_surface_gives_a_red_diffuse_term() {
+    // Pared blanca solo difusa frente a una luz roja puesta en el ojo, que le llega de frente
+    let white = flat_material(Color::new(255, 255, 255), [0.9, 0.0, 0.0, 0.0]);
+    let objects: Vec<Box<dyn RayIntersect>> = vec![Box::new(Plane::new(Vec3::new(0.0, 0.0, -2.0), Vec3::new(0.0, 0.0, 1.0), white))];
+    let lights = vec![Light::new(Vec3::zeros(), Color::new(255, 0, 0), 1.0)];
+
+    let breakdown = cast_ray_breakdown(&Vec3::zeros(), &Vec3::new(0.0, 0.0, -1.0), &objects, &lights, &RenderConfig::default(), 0);
+
+    // La luz tiñe el difuso: 255 * 0.9 en rojo y nada en verde ni azul
+    let diffuse = breakdown.diffuse;
+    assert!(diffuse.r.abs_diff(229) <= 1, "{:?}", diffuse);
+    assert_eq!((diffuse.g, diffuse.b), (0, 0));
+    assert_eq!((breakdown.color.r, breakdown.color.g, breakdown.color.b), (diffuse.r, 0, 0));
+}
+
+#[test]
+fn dither_breaks_up_bands_between_code_values() {
+    // Un fondo gris que con la exposición queda justo entre dos valores de código (100.5)
+    let background = Background::Solid(Color::new(201, 201, 201));
+    let render_gray = |dither: f32| {
+        let config = RenderConfig { background: background.clone(), exposure: 0.5, dither, ..RenderConfig::default() };
+        let mut framebuffer = vec![0u32; WIDTH * HEIGHT];
+        render(&mut framebuffer, WIDTH, HEIGHT, &[], &small_scene().2, &[], &config);
+        framebuffer.iter().map(|pixel| (pixel & 0xFF) as f32).collect::<Vec<_>>()
+    };
+
+    // Sin tramado todo el fondo cae en un solo valor: el escalón de una banda
+    let flat = render_gray(0.0);
+    assert!(flat.iter().all(|value| *value == flat[0]));
+
+    // Con tramado se mezclan los dos valores vecinos y el promedio conserva el nivel intermedio
+    let dithered = render_gray(0.5);
+    let mean = dithered.iter().sum::<f32>() / dithered.len() as f32;
+    assert!(dithered.iter().all(|value| *value == 100.0 || *value == 101.0));
+    assert!((mean - 100.5).abs() < 0.1, "mean {}", mean);
+}
+
+#[test]
+fn lens_matches_the_equivalent_fov() {
+    // 35 mm sobre un sensor de formato completo equivale a unos 37.8° de campo vertical
+    let lens = Lens { focal_length: 35.0, sensor_height: 24.0 };
+    assert!((lens.fov() - 37.849).abs() < 1e-2, "fov {}", lens.fov());
+
+    let (objects, lights, camera) = small_scene();
+    let with_fov = Camera { fov: lens.fov(), lens: None, ..camera.clone() };
+    let with_lens = Camera { lens: Some(lens), ..camera };
+    assert!((with_fov.half_height() - with_lens.half_height()).abs() < 1e-6);
+
+    let mut from_fov = vec![0u32; WIDTH * HEIGHT];
+    let mut from_lens = vec![0u32; WIDTH * HEIGHT];
+    render(&mut from_fov, WIDTH, HEIGHT, &objects, &with_fov, &lights, &RenderConfig::default());
+    render(&mut from_lens, WIDTH, HEIGHT, &objects, &with_lens, &lights, &RenderConfig::default());
+    assert_eq!(from_fov, from_lens);
+}
+
+#[test]
+fn sphere_stays_round_in_a_wide_window() {
+    let (width, height) = (96, 48);
+    let objects: Vec<Box<dyn RayIntersect>> = vec![Box::new(Sphere::new(
+        Vec3::new(0.0, 0.0, 0.0),
+        1.0,
+        flat_material(Color::new(255, 255, 255), [1.0, 0.0, 0.0, 0.0]),
+    ))];
+    // Una luz en el ojo ilumina todo el disco visible
+    let lights = vec![Light::new(Vec3::new(0.0, 0.0, 5.0), Color::new(255, 255, 255), 1.0)];
+    let config = RenderConfig { background: Background::Solid(Color::new(0, 0, 0)), ..RenderConfig::default() };
+
+    for fov in [DEFAULT_FOV, 40.0] {
+        let camera = Camera {
+            eye: Vec3::new(0.0, 0.0, 5.0),
+            center: Vec3::zeros(),
+            up: Vec3::new(0.0, 1.0, 0.0),
+            fov,
+            lens: None,
+        };
+        let mut framebuffer = vec![0u32; width * height];
+        render(&mut framebuffer, width, height, &objects, &camera, &lights, &config);
+
+        let (sphere_width, sphere_height) = lit_extent(&framebuffer, width);
+        assert!(sphere_width.abs_diff(sphere_height) <= 1, "fov {}: {}x{} px", fov, sphere_width, sphere_height);
+    }
+}
+
+// Cuadrado de dos triángulos en z = 0, con la normal de la cara hacia +z
+fn quad(material: Material) -> Vec<Box<dyn RayIntersect>> {
+    let (a, b) = (Vec3::new(-1.0, -1.0, 0.0), Vec3::new(1.0, -1.0, 0.0));
+    let (c, d) = (Vec3::new(1.0, 1.0, 0.0), Vec3::new(-1.0, 1.0, 0.0));
+    vec![Box::new(Triangle::new([a, b, c], material.clone())), Box::new(Triangle::new([a, c, d], material))]
+}
+
+#[test]
+fn quad_is_lit_from_either_side() {
+    let objects = quad(flat_material(Color::new(200, 200, 200), [0.9, 0.1, 0.0, 0.0]));
+    let config = RenderConfig::default();
+    let lit_from = |z: f32| vec![Light::new(Vec3::new(0.0, 0.0, z), Color::new(255, 255, 255), 1.0)];
+    let rgb = |color: Color| (color.r, color.g, color.b);
+
+    // Visto y alumbrado desde atrás se ve igual que visto y alumbrado desde adelante
+    let front = cast_ray(&Vec3::new(0.0, 0.0, 3.0), &Vec3::new(0.0, 0.0, -1.0), &objects, &lit_from(3.0), &config, 0);
+    let back = cast_ray(&Vec3::new(0.0, 0.0, -3.0), &Vec3::new(0.0, 0.0, 1.0), &objects, &lit_from(-3.0), &config, 0);
+    assert!(front.r > 150, "{:?}", rgb(front));
+    assert_eq!(rgb(back), rgb(front));
+
+    // La luz del otro lado no atraviesa el cuadrado
+    let unlit = cast_ray(&Vec3::new(0.0, 0.0, -3.0), &Vec3::new(0.0, 0.0, 1.0), &objects, &lit_from(3.0), &config, 0);
+    assert_eq!(rgb(unlit), (0, 0, 0));
+}
+
+#[test]
+fn back_of_a_quad_receives_shadows() {
+    let mut objects = quad(flat_material(Color::new(200, 200, 200), [0.9, 0.1, 0.0, 0.0]));
+    let lights = vec![Light::new(Vec3::new(0.0, 0.0, -3.0), Color::new(255, 255, 255), 1.0)];
+    let config = RenderConfig::default();
+    let (origin, direction) = (Vec3::new(0.8, 0.0, -3.0), Vec3::new(0.0, 0.0, 1.0));
+    let lit = cast_ray(&origin, &direction, &objects, &lights, &config, 0);
+
+    // Un cubo entre la luz y el punto (0.8, 0, 0) que el rayo de cámara no toca
+    objects.push(cube(Vec3::new(0.0, 0.0, -1.0), 1.2, flat_material(Color::new(200, 200, 200), [0.9, 0.1, 0.0, 0.0])));
+    let shadowed = cast_ray(&origin, &direction, &objects, &lights, &config, 0);
+    // La sombra se aclara con la distancia al bloqueador, así que no llega a negro
+    assert!(lit.r > 150, "{}", lit.r);
+    assert!(shadowed.r < lit.r / 2, "{} vs {}", shadowed.r, lit.r);
+}
+
+#[test]
+fn sphere_texture_has_no_seam_where_u_wraps() {
+    // Textura con mips que solo cambia con v: cualquier diferencia a los lados de la costura en u sería
+    // un nivel de mip o un texel distinto
+    let rows = (0..16 * 16).map(|i| Color::new((i / 16 * 16) as u8, 128, 255 - (i / 16 * 16) as u8)).collect();
+    let mut material = flat_material(Color::new(255, 255, 255), [1.0, 0.0, 0.0, 0.0]);
+    material.texture = Some(Arc::new(Texture::new(16, 16, rows).with_mipmaps()));
+    let objects: Vec<Box<dyn RayIntersect>> = vec![Box::new(Sphere::new(Vec3::zeros(), 1.0, material))];
+    // Luz ambiental: el sombreado no depende de la normal
+    let lights = vec![Light::new(Vec3::zeros(), Color::new(255, 255, 255), 0.3)];
+    // La costura (u = 0 = 1) es el meridiano del lado -x, que la cámara ve de frente en la columna central
+    let camera = Camera {
+        eye: Vec3::new(-3.0, 0.0, 0.0),
+        center: Vec3::zeros(),
+        up: Vec3::new(0.0, 1.0, 0.0),
+        fov: DEFAULT_FOV,
+        lens: None,
+    };
+    let mut framebuffer = vec![0u32; WIDTH * HEIGHT];
+    render(&mut framebuffer, WIDTH, HEIGHT, &objects, &camera, &lights, &RenderConfig::default());
+
+    let seam = WIDTH / 2;
+    for y in HEIGHT / 4..HEIGHT * 3 / 4 {
+        let row = &framebuffer[y * WIDTH..(y + 1) * WIDTH];
+        for k in 1..4 {
+            assert_eq!(row[seam - k], row[seam + k], "row {}, columns {} and {}", y, seam - k, seam + k);
+        }
+        assert_eq!(row[seam], row[seam + 1], "row {}: seam column", y);
+    }
+}
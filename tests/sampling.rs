@@ -0,0 +1,34 @@
+use nalgebra_glm::Vec3;
+use ray_tracing::sampling::{hash, orthonormal_basis, sample_hemisphere_cosine};
+
+const SAMPLES: u32 = 20_000;
+
+#[test]
+fn orthonormal_basis_is_orthonormal() {
+    for normal in [Vec3::new(0.0, 1.0, 0.0), Vec3::new(0.0, -1.0, 0.0), Vec3::new(0.3, -0.5, 0.8).normalize()] {
+        let (tangent, bitangent) = orthonormal_basis(&normal);
+        for (a, b) in [(tangent, bitangent), (tangent, normal), (bitangent, normal)] {
+            assert!(a.dot(&b).abs() < 1e-5);
+        }
+        for v in [tangent, bitangent] {
+            assert!((v.magnitude() - 1.0).abs() < 1e-5);
+        }
+    }
+}
+
+#[test]
+fn cosine_samples_stay_in_the_hemisphere_and_center_on_the_normal() {
+    let normal = Vec3::new(0.4, 0.2, -0.9).normalize();
+    let mut sum = Vec3::zeros();
+    for i in 0..SAMPLES {
+        let direction = sample_hemisphere_cosine(&normal, hash(i));
+        assert!(direction.dot(&normal) >= 0.0);
+        assert!((direction.magnitude() - 1.0).abs() < 1e-4);
+        sum += direction;
+    }
+
+    // Para la distribución coseno la media de cos(θ) es 2/3 y la media apunta en la dirección de la normal
+    let mean = sum / SAMPLES as f32;
+    assert!(mean.normalize().dot(&normal) > 0.999);
+    assert!((mean.dot(&normal) - 2.0 / 3.0).abs() < 0.02);
+}
@@ -0,0 +1,157 @@
+use nalgebra_glm::Vec3;
+use std::sync::Arc;
+
+use ray_tracing::camera::{Camera, DEFAULT_FOV};
+use ray_tracing::color::Color;
+use ray_tracing::cube::Cube;
+use ray_tracing::intersect::RayIntersect;
+use ray_tracing::light::Light;
+use ray_tracing::material::{Material, MaterialLibrary};
+use ray_tracing::scene::{load_from_json, load_from_json_with, Scene, SceneError};
+use ray_tracing::sphere::Sphere;
+use ray_tracing::texture::Texture;
+
+// Escribe `contents` en un archivo temporal con nombre propio de la prueba y devuelve su ruta
+fn temp_file(name: &str, contents: &str) -> String {
+    let path = std::env::temp_dir().join(format!("ray_tracing_{}_{}.json", name, std::process::id()));
+    std::fs::write(&path, contents).expect("write temp scene");
+    path.to_string_lossy().into_owned()
+}
+
+#[test]
+fn example_scene_loads_with_named_materials() {
+    let path = format!("{}/scenes/ejemplo.json", env!("CARGO_MANIFEST_DIR"));
+    let scene = load_from_json(&path).expect("example scene");
+
+    assert_eq!(scene.objects.len(), 5);
+    assert_eq!(scene.lights.len(), 2);
+    assert_eq!(scene.camera.fov, DEFAULT_FOV);
+    assert!(scene.validate().is_empty(), "{:?}", scene.validate());
+
+    // La esfera de vidrio toma el material de la biblioteca por su nombre
+    let glass = scene.objects[3].as_any().downcast_ref::<Sphere>().expect("sphere");
+    assert_eq!(glass.material.refractive_index, Material::glass().refractive_index);
+}
+
+#[test]
+fn unknown_material_reports_its_key() {
+    let path = temp_file(
+        "unknown_material",
+        r#"{
+            "camera": { "eye": [0.0, 0.0, 5.0], "center": [0.0, 0.0, 0.0] },
+            "lights": [],
+            "objects": [
+                { "type": "sphere", "center": [0.0, 0.0, 0.0], "radius": 1.0, "material": "glass" },
+                { "type": "cube", "center": [3.0, 0.0, 0.0], "size": 1.0, "materials": ["lava"] }
+            ]
+        }"#,
+    );
+
+    let result = load_from_json(&path);
+    let _ = std::fs::remove_file(&path);
+    match result {
+        Err(SceneError::UnknownMaterial { key, name, .. }) => {
+            assert_eq!(key, "objects[1].materials[0]");
+            assert_eq!(name, "lava");
+        }
+        other => panic!("expected an unknown material error, got {:?}", other.map(|scene| scene.objects.len())),
+    }
+}
+
+#[test]
+fn saved_scene_loads_back() {
+    let camera = Camera {
+        eye: Vec3::new(1.0, 2.0, 3.0),
+        center: Vec3::zeros(),
+        up: Vec3::new(0.0, 1.0, 0.0),
+        fov: 60.0,
+        lens: None,
+    };
+    let objects: Vec<Box<dyn RayIntersect>> = vec![Box::new(Sphere::new(Vec3::new(0.0, 1.0, 0.0), 0.5, Material::chrome()))];
+    let lights = vec![Light::new(Vec3::new(0.0, 5.0, 0.0), Color::new(255, 200, 100), 1.2)];
+    let path = temp_file("round_trip", "");
+    Scene::new(objects, lights, camera).save_json(&path).expect("save scene");
+
+    let loaded = load_from_json(&path).expect("load scene");
+    let _ = std::fs::remove_file(&path);
+    assert_eq!(loaded.camera.eye, Vec3::new(1.0, 2.0, 3.0));
+    assert!((loaded.camera.fov - 60.0).abs() < 1e-4);
+    assert_eq!(loaded.lights[0].color.g, 200);
+    let sphere = loaded.objects[0].as_any().downcast_ref::<Sphere>().expect("sphere");
+    assert_eq!(sphere.radius, 0.5);
+    assert_eq!(sphere.material.albedo, Material::chrome().albedo);
+}
+
+#[test]
+fn saved_scene_keeps_named_materials_and_shadow_flags() {
+    let camera = Camera { eye: Vec3::new(0.0, 0.0, 5.0), center: Vec3::zeros(), up: Vec3::new(0.0, 1.0, 0.0), fov: 60.0, lens: None };
+    let textured = Material {
+        texture: Some(Arc::new(Texture::from_color(Color::new(200, 100, 50)))),
+        ..Material::matte_white()
+    };
+    let mut library = MaterialLibrary::with_presets();
+    library.insert("ladrillo", textured.clone());
+
+    // Un cubo con el material texturizado de la biblioteca que no proyecta sombra, una esfera con un
+    // preset y otra con una textura sin nombre, que solo puede guardarse sin ella
+    let mut cube = Cube::uniform(Vec3::zeros(), 1.0, library.instance("ladrillo").expect("library material"));
+    cube.casts_shadow = false;
+    let objects: Vec<Box<dyn RayIntersect>> = vec![
+        Box::new(cube),
+        Box::new(Sphere::new(Vec3::new(2.0, 0.0, 0.0), 0.5, library.instance("chrome").expect("preset"))),
+        Box::new(Sphere::new(Vec3::new(-2.0, 0.0, 0.0), 0.5, textured)),
+    ];
+    let path = temp_file("named_materials", "");
+    let warnings = Scene::new(objects, Vec::new(), camera).save_json(&path).expect("save scene");
+    assert_eq!(warnings, vec!["object 2: 1 material fields not saved (texture)".to_string()]);
+
+    // El preset también queda en la tabla del archivo, así que carga sin la biblioteca
+    let text = std::fs::read_to_string(&path).expect("saved scene");
+    let loaded = load_from_json_with(&path, &library).expect("load scene");
+    let _ = std::fs::remove_file(&path);
+    assert!(text.contains("\"chrome\": {"), "{}", text);
+    assert!(!text.contains("\"ladrillo\": {"), "{}", text);
+
+    let cube = loaded.objects[0].as_any().downcast_ref::<Cube>().expect("cube");
+    assert!(!cube.casts_shadow);
+    assert!(cube.materials.iter().all(|material| material.texture.is_some()));
+    assert_eq!(cube.materials[0].name.as_deref(), Some("ladrillo"));
+    let chrome = loaded.objects[1].as_any().downcast_ref::<Sphere>().expect("sphere");
+    assert_eq!(chrome.material.albedo, Material::chrome().albedo);
+    let unnamed = loaded.objects[2].as_any().downcast_ref::<Sphere>().expect("sphere");
+    assert!(unnamed.material.texture.is_none());
+}
+
+#[test]
+fn saved_scene_keeps_tweaked_copies_of_named_materials() {
+    let camera = Camera { eye: Vec3::new(0.0, 0.0, 5.0), center: Vec3::zeros(), up: Vec3::new(0.0, 1.0, 0.0), fov: 60.0, lens: None };
+    let mut library = MaterialLibrary::with_presets();
+    library.insert(
+        "ladrillo",
+        Material { texture: Some(Arc::new(Texture::from_color(Color::new(200, 100, 50)))), ..Material::matte_white() },
+    );
+    let chrome = library.instance("chrome").expect("preset");
+    let dull_chrome = Material { specular: 5.0, ..chrome.clone() };
+    let dark_brick = Material { albedo: [0.2, 0.0, 0.0, 0.0], ..library.instance("ladrillo").expect("library material") };
+
+    // Dos esferas con el mismo retoque, una con el original y un ladrillo retocado, que no se puede guardar
+    let sphere = |x: f32, material: &Material| -> Box<dyn RayIntersect> {
+        Box::new(Sphere::new(Vec3::new(x, 0.0, 0.0), 0.5, material.clone()))
+    };
+    let objects: Vec<Box<dyn RayIntersect>> =
+        vec![sphere(-2.0, &dull_chrome), sphere(0.0, &chrome), sphere(2.0, &dull_chrome), sphere(4.0, &dark_brick)];
+    let path = temp_file("tweaked_materials", "");
+    let warnings = Scene::new(objects, Vec::new(), camera).save_json_with(&path, &library).expect("save scene");
+    assert_eq!(warnings, vec!["object 3: changes to textured material \"ladrillo\" not saved".to_string()]);
+
+    let loaded = load_from_json_with(&path, &library).expect("load scene");
+    let _ = std::fs::remove_file(&path);
+    let material = |i: usize| &loaded.objects[i].as_any().downcast_ref::<Sphere>().expect("sphere").material;
+    assert_eq!(material(0).name.as_deref(), Some("chrome#2"));
+    assert_eq!(material(0).specular, 5.0);
+    assert_eq!(material(1).name.as_deref(), Some("chrome"));
+    assert_eq!(material(1).specular, chrome.specular);
+    assert_eq!(material(2).name.as_deref(), Some("chrome#2"));
+    assert_eq!(material(3).name.as_deref(), Some("ladrillo"));
+    assert!(material(3).texture.is_some());
+}
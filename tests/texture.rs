@@ -0,0 +1,80 @@
+use ray_tracing::color::Color;
+use ray_tracing::texture::Texture;
+
+fn gray(value: u8) -> Color {
+    Color::new(value, value, value)
+}
+
+fn rgb(color: &Color) -> (u8, u8, u8) {
+    (color.r, color.g, color.b)
+}
+
+// Colores de los texels de una textura, para compararlos
+fn texels(texture: &Texture) -> Vec<(u8, u8, u8)> {
+    texture.data.iter().map(rgb).collect()
+}
+
+// Tablero de `size` x `size` texels alternando negro y blanco
+fn checker(size: usize) -> Texture {
+    let data = (0..size * size).map(|i| if (i % size + i / size).is_multiple_of(2) { gray(0) } else { gray(255) }).collect();
+    Texture::new(size, size, data)
+}
+
+#[test]
+fn two_by_two_texture_averages_to_its_one_texel_mip() {
+    let texture = Texture::new(2, 2, vec![Color::new(40, 0, 0), Color::new(0, 80, 0), Color::new(0, 0, 120), Color::new(40, 80, 120)]).with_mipmaps();
+
+    assert_eq!(texture.mips.len(), 1);
+    assert_eq!((texture.mips[0].width, texture.mips[0].height), (1, 1));
+    assert_eq!(texels(&texture.mips[0]), vec![(20, 40, 60)]);
+}
+
+#[test]
+fn mip_chain_halves_each_side_down_to_one_texel() {
+    let sizes = |texture: &Texture| texture.mips.iter().map(|mip| (mip.width, mip.height)).collect::<Vec<_>>();
+
+    assert_eq!(sizes(&Texture::new(8, 4, vec![gray(0); 32]).with_mipmaps()), vec![(4, 2), (2, 1), (1, 1)]);
+    assert_eq!(sizes(&Texture::new(5, 3, vec![gray(0); 15]).with_mipmaps()), vec![(2, 1), (1, 1)]);
+    assert!(Texture::from_color(gray(0)).with_mipmaps().mips.is_empty());
+}
+
+#[test]
+fn odd_side_keeps_its_last_texel() {
+    // La última columna de un lado impar entra en el promedio en vez de perderse
+    let texture = Texture::new(3, 1, vec![gray(0), gray(0), gray(90)]).with_mipmaps();
+    assert_eq!(texels(&texture.mips[0]), vec![(30, 30, 30)]);
+
+    let texture = Texture::new(1, 3, vec![gray(0), gray(0), gray(90)]).with_mipmaps();
+    assert_eq!(texels(&texture.mips[0]), vec![(30, 30, 30)]);
+}
+
+#[test]
+fn chosen_level_grows_with_the_footprint() {
+    let texture = checker(8).with_mipmaps();
+
+    // Un texel del tablero de 8 son 1/8 de UV; cada vez que el rayo cubre el doble se sube un nivel
+    let levels: Vec<f32> = [0.0, 1.0 / 8.0, 2.0 / 8.0, 4.0 / 8.0, 1.0].iter().map(|footprint| texture.mip_level(*footprint)).collect();
+    assert_eq!(levels, vec![0.0, 0.0, 1.0, 2.0, 3.0]);
+
+    // Un rayo fino ve los texels del tablero; uno ancho, su promedio gris. Más allá del último nivel
+    // se queda en el de 1x1.
+    let (u, v) = (0.5 / 8.0, 1.0 - 0.5 / 8.0);
+    assert_eq!(rgb(&texture.sample_footprint(u, v, 0.0)), (0, 0, 0));
+    assert_eq!(rgb(&texture.sample_footprint(u, v, 2.0 / 8.0)), (127, 127, 127));
+    assert_eq!(rgb(&texture.sample_footprint(u, v, 100.0)), rgb(&texture.mips[2].data[0]));
+}
+
+#[test]
+fn negative_uv_wraps_around() {
+    // Cada texel de 4x4 con su propio gris: columna x y fila y (contando desde arriba) dan 10 * x + 50 * y
+    let texture = Texture::new(4, 4, (0..16).map(|i| gray((i % 4 * 10 + i / 4 * 50) as u8)).collect());
+    let at = |x: u8, y: u8| (10 * x + 50 * y, 10 * x + 50 * y, 10 * x + 50 * y);
+
+    // Un poco antes de u = 0 se repite la última columna, no la primera
+    assert_eq!(rgb(&texture.sample(-0.1, 0.9)), at(3, 0));
+    assert_eq!(rgb(&texture.sample(-1.1, 0.9)), at(3, 0));
+    assert_eq!(rgb(&texture.sample(-1e-9, 0.9)), at(3, 0));
+    // Pasado v = 1 se repite la fila de abajo, y antes de v = 0 la de arriba
+    assert_eq!(rgb(&texture.sample(0.1, 1.1)), at(0, 3));
+    assert_eq!(rgb(&texture.sample(0.1, -0.2)), at(0, 0));
+}
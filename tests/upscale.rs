@@ -0,0 +1,87 @@
+use nalgebra_glm::Vec3;
+use ray_tracing::camera::Camera;
+use ray_tracing::gbuffer::GBuffer;
+use ray_tracing::intersect::RayIntersect;
+use ray_tracing::material::Material;
+use ray_tracing::plane::Plane;
+use ray_tracing::render::RenderConfig;
+use ray_tracing::sphere::Sphere;
+use ray_tracing::upscale::upscale_edge_aware;
+
+const RED: u32 = 0xFF0000;
+const BLUE: u32 = 0x0000FF;
+
+// G-buffer de `width` x `height` con un objeto cercano a la izquierda de la columna `edge` y otro más
+// lejano a la derecha, los dos mirando a la cámara
+fn two_objects(width: usize, height: usize, edge: usize) -> GBuffer {
+    GBuffer {
+        width,
+        height,
+        normals: vec![Vec3::new(0.0, 0.0, 1.0); width * height],
+        depths: (0..width * height).map(|i| if i % width < edge { 5.0 } else { 10.0 }).collect(),
+    }
+}
+
+#[test]
+fn pixel_next_to_a_silhouette_only_mixes_its_own_surface() {
+    // En baja resolución el objeto cercano ocupa las columnas 0 y 1; en alta, las columnas 0 a 3
+    let low = two_objects(4, 2, 2);
+    let high = two_objects(8, 4, 4);
+    let framebuffer: Vec<u32> = (0..8).map(|i| if i % 4 < 2 { RED } else { BLUE }).collect();
+
+    let upscaled = upscale_edge_aware(&framebuffer, &low, &high);
+
+    // El píxel 3 cae a mitad de camino entre la columna 1 (roja) y la 2 (azul): un bilineal daría
+    // violeta, pero es del objeto cercano y solo toma sus muestras. El 4 ya es del lejano.
+    for y in 0..4 {
+        assert_eq!(upscaled[y * 8 + 3], RED, "row {}", y);
+        assert_eq!(upscaled[y * 8 + 4], BLUE, "row {}", y);
+    }
+}
+
+#[test]
+fn unmatched_pixel_falls_back_to_the_nearest_sample() {
+    let low = two_objects(4, 2, 2);
+    let mut high = two_objects(8, 4, 4);
+    // Un píxel de fondo rodeado de muestras de objetos no se parece a ninguna
+    high.depths[3] = f32::INFINITY;
+    high.normals[3] = Vec3::zeros();
+    let framebuffer: Vec<u32> = (0..8).map(|i| (i as u32 + 1) * 0x10).collect();
+
+    let upscaled = upscale_edge_aware(&framebuffer, &low, &high);
+
+    // Está en x = 1.5, y = 0 de la baja resolución: la muestra más cercana es la columna 2 de la fila 0
+    assert_eq!(upscaled[3], framebuffer[2]);
+}
+
+#[test]
+fn guided_gbuffer_matches_the_full_one_at_silhouettes() {
+    let objects: Vec<Box<dyn RayIntersect>> = vec![
+        Box::new(Sphere::new(Vec3::zeros(), 1.0, Material::matte_white())),
+        Box::new(Plane::new(Vec3::new(0.0, -1.0, 0.0), Vec3::new(0.0, 1.0, 0.0), Material::matte_white())),
+    ];
+    let camera = Camera { eye: Vec3::new(0.0, 0.5, 4.0), center: Vec3::zeros(), up: Vec3::new(0.0, 1.0, 0.0), fov: 60.0, lens: None };
+    let config = RenderConfig::default();
+
+    let low = GBuffer::render(32, 24, &objects, &camera, &config);
+    let full = GBuffer::render(64, 48, &objects, &camera, &config);
+    let guided = GBuffer::render_guided(&low, 64, 48, &objects, &camera, &config);
+
+    // Gris según la profundidad, para que el escalado dependa de la geometría de cada muestra
+    let framebuffer: Vec<u32> = low
+        .depths
+        .iter()
+        .map(|depth| if depth.is_finite() { (*depth * 40.0).min(255.0) as u32 * 0x010101 } else { 0 })
+        .collect();
+    let from_full = upscale_edge_aware(&framebuffer, &low, &full);
+    let from_guided = upscale_edge_aware(&framebuffer, &low, &guided);
+
+    // Fuera de los bordes el G-buffer guiado copia muestras en vez de lanzar rayos, lo que apenas cambia
+    // los pesos; en las siluetas lanza los mismos rayos que el completo
+    let channel = |pixel: u32, shift: u32| ((pixel >> shift) & 0xFF) as i32;
+    for (i, (a, b)) in from_full.iter().zip(&from_guided).enumerate() {
+        for shift in [16, 8, 0] {
+            assert!((channel(*a, shift) - channel(*b, shift)).abs() <= 4, "pixel {}: {:06x} vs {:06x}", i, a, b);
+        }
+    }
+}